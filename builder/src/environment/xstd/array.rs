@@ -53,6 +53,27 @@ pub fn register(env: &mut EnvironmentBuilder) {
     array_number_with_size!(env, u256, U256);
 }
 
+// Index an array modulo its length
+pub fn register_get_wrapping(env: &mut EnvironmentBuilder) {
+    env.register_native_function("get_wrapping", Some(Type::Array(Box::new(Type::T(Some(0))))), vec![("index", Type::U32)], get_wrapping, 1, Some(Type::T(Some(0))));
+}
+
+// Truncate or pad an array with a fill value
+pub fn register_resize(env: &mut EnvironmentBuilder) {
+    env.register_native_function("resize", Some(Type::Array(Box::new(Type::T(Some(0))))), vec![("new_len", Type::U32), ("fill", Type::T(Some(0)))], resize, 5, None);
+}
+
+// Only arrays of comparable primitives can be sorted / deduplicated
+pub fn register_sort_dedup(env: &mut EnvironmentBuilder) {
+    env.register_native_function("sort", Some(Type::Array(Box::new(Type::T(Some(0))))), vec![], sort, 5, None);
+    env.register_native_function("dedup", Some(Type::Array(Box::new(Type::T(Some(0))))), vec![], dedup, 5, None);
+}
+
+// Find the first index of a value
+pub fn register_index_of(env: &mut EnvironmentBuilder) {
+    env.register_native_function("index_of", Some(Type::Array(Box::new(Type::T(Some(0))))), vec![("value", Type::T(Some(0)))], index_of, 10, Some(Type::Optional(Box::new(Type::U32))));
+}
+
 // native functions
 fn len(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
     let len = zelf?.as_vec()?.len();
@@ -112,12 +133,18 @@ fn slice(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> F
 
     let vec = zelf?.as_mut_vec()?;
     let len = vec.len() as u32;
-    if start >= len || end >= len || start >= end {
+    // end is exclusive, an empty slice is allowed
+    if start > end {
         return Err(EnvironmentError::InvalidRange(start, end))
     }
 
+    if end > len {
+        return Err(EnvironmentError::OutOfBounds(end as usize, len as usize))
+    }
+
     // we need to go through all elements in the slice, thus we increase the gas usage
-    context.increase_gas_usage((vec.len() as u64) * 5)?;
+    // memory of the new array is accounted when it is returned
+    context.increase_gas_usage(((end - start) as u64) * 5)?;
 
     let mut slice = Vec::new();
     for i in start..end {
@@ -132,15 +159,34 @@ fn slice(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> F
     Ok(Some(ValueCell::Object(slice)))
 }
 
+// Find the index of the first element equal to the value
+// Gas is charged for each element scanned
+fn position(vec: &[ValueCell], value: &ValueCell, context: &mut Context) -> Result<Option<usize>, EnvironmentError> {
+    let position = vec.iter().position(|v| v == value);
+    let scanned = position.map(|i| i + 1).unwrap_or(vec.len());
+    context.increase_gas_usage((scanned as u64) * 5)?;
+
+    Ok(position)
+}
+
 fn contains(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
     let value = parameters.remove(0);
     let handle = value.as_ref()?;
     let vec = zelf?.as_vec()?;
 
-    // we need to go through all elements in the slice, thus we increase the gas usage
-    context.increase_gas_usage((vec.len() as u64) * 5)?;
+    let found = position(vec, handle, context)?.is_some();
+    Ok(Some(Primitive::Boolean(found).into()))
+}
 
-    Ok(Some(Primitive::Boolean(vec.iter().find(|v| **v == *handle).is_some()).into()))
+fn index_of(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let value = parameters.remove(0);
+    let handle = value.as_ref()?;
+    let vec = zelf?.as_vec()?;
+
+    Ok(Some(match position(vec, handle, context)? {
+        Some(index) => Primitive::U32(index as u32).into(),
+        None => Primitive::Null.into()
+    }))
 }
 
 fn get(zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
@@ -153,6 +199,18 @@ fn get(zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnT
     }
 }
 
+// Get the element at `index % len`
+// Only an empty array returns an error
+fn get_wrapping(zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
+    let index = parameters.remove(0).as_u32()? as usize;
+    let vec = zelf?.as_vec()?;
+    if vec.is_empty() {
+        return Err(EnvironmentError::OutOfBounds(index, 0))
+    }
+
+    Ok(Some(vec[index % vec.len()].clone()))
+}
+
 fn first(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
     let vec = zelf?.as_vec()?;
     if let Some(value) = vec.first() {
@@ -188,6 +246,93 @@ fn extend(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) ->
     Ok(None)
 }
 
+// Truncate the array to `new_len` elements
+// or pad it with `fill` until it reaches `new_len` elements
+fn resize(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let new_len = parameters.remove(0).as_u32()? as usize;
+
+    let param = parameters.remove(0);
+    let depth = param.depth();
+    let fill = param.into_owned()?;
+
+    let array = zelf?.as_mut_vec()?;
+    if new_len > array.len() {
+        fill.calculate_depth(
+            context.max_value_depth()
+                .saturating_sub(depth.saturating_add(1))
+        )?;
+
+        let added = new_len - array.len();
+        let memory = fill.calculate_memory_usage(context.memory_left())?;
+        context.increase_gas_usage(added as _)?;
+        context.increase_memory_usage(memory.saturating_mul(added))?;
+
+        array.resize(new_len, fill);
+    } else {
+        context.increase_gas_usage((array.len() - new_len) as _)?;
+        array.truncate(new_len);
+    }
+
+    Ok(None)
+}
+
+// Verify that all the elements are primitives comparable between them
+fn verify_comparable(array: &[ValueCell]) -> Result<(), EnvironmentError> {
+    if let Some(first) = array.first() {
+        let first = first.as_value()?;
+        for value in array.iter() {
+            if value.as_value()?.partial_cmp(first).is_none() {
+                return Err(EnvironmentError::InvalidType)
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn sort(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let array = zelf?.as_mut_vec()?;
+    verify_comparable(array)?;
+
+    // sorting is O(n log n)
+    let len = array.len() as u64;
+    let log = len.checked_ilog2().unwrap_or(0) as u64 + 1;
+    context.increase_gas_usage(len * log)?;
+
+    // All elements were verified as comparable above
+    array.sort_by(|a, b| a.as_value()
+        .ok()
+        .zip(b.as_value().ok())
+        .and_then(|(a, b)| a.partial_cmp(b))
+        .unwrap_or(std::cmp::Ordering::Equal)
+    );
+
+    Ok(None)
+}
+
+// Remove consecutive duplicates, call sort before to remove all duplicates
+fn dedup(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let array = zelf?.as_mut_vec()?;
+    verify_comparable(array)?;
+
+    context.increase_gas_usage(array.len() as _)?;
+
+    let mut values: Vec<ValueCell> = Vec::with_capacity(array.len());
+    let mut freed = 0usize;
+    for value in array.drain(..) {
+        if values.last() == Some(&value) {
+            freed = freed.saturating_add(value.calculate_memory_usage(usize::MAX)?);
+        } else {
+            values.push(value);
+        }
+    }
+
+    *array = values;
+    context.decrease_memory_usage(freed);
+
+    Ok(None)
+}
+
 fn concat(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
     let vec = zelf?.as_vec()?;
     context.increase_gas_usage(vec.len() as u64)?;
@@ -2,6 +2,31 @@ use tos_environment::{Context, EnvironmentError, FnInstance, FnParams, FnReturnT
 use tos_types::{Constant, Primitive, Type, ValueCell};
 
 use crate::EnvironmentBuilder;
+use super::string::decode_hex;
+
+// Size of a hash in bytes
+const HASH_SIZE: usize = 32;
+
+// Lookup table for the standard CRC-32 (IEEE 802.3) reflected polynomial
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
 
 pub fn register(env: &mut EnvironmentBuilder) {
     // Bytes
@@ -27,6 +52,33 @@ pub fn register(env: &mut EnvironmentBuilder) {
     });
 }
 
+// Compute the CRC-32 checksum of bytes
+pub fn register_crc32(env: &mut EnvironmentBuilder) {
+    env.register_native_function("crc32", Some(Type::Bytes), vec![], crc32, 5, Some(Type::U32));
+}
+
+// LEB128 variable-length integers
+pub fn register_varint(env: &mut EnvironmentBuilder) {
+    env.register_native_function("read_varint", None, vec![("bytes", Type::Bytes), ("offset", Type::U32)], read_varint, 5, Some(Type::Optional(Box::new(Type::Tuples(vec![Type::U64, Type::U64])))));
+    env.register_native_function("write_varint", None, vec![("value", Type::U64)], write_varint, 5, Some(Type::Bytes));
+}
+
+// Short identifiers derived from a 32 bytes hash
+pub fn register_hash_prefix(env: &mut EnvironmentBuilder) {
+    env.register_native_function("hash_prefix", None, vec![("hash", Type::Bytes), ("len", Type::U32)], hash_prefix, 1, Some(Type::Bytes));
+}
+
+// Hex conversions, both lowercase and uppercase are accepted when decoding
+pub fn register_hex(env: &mut EnvironmentBuilder) {
+    env.register_native_function("to_hex", None, vec![("bytes", Type::Bytes)], to_hex, 5, Some(Type::String));
+    env.register_native_function("from_hex", None, vec![("value", Type::String)], from_hex, 5, Some(Type::Optional(Box::new(Type::Bytes))));
+}
+
+// Check if the bytes are a valid UTF-8 sequence
+pub fn register_is_valid_utf8(env: &mut EnvironmentBuilder) {
+    env.register_native_function("is_valid_utf8", None, vec![("bytes", Type::Bytes)], is_valid_utf8, 1, Some(Type::Bool));
+}
+
 // native functions
 fn len(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
     let len = zelf?.as_bytes()?.len();
@@ -140,4 +192,133 @@ fn to_array(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnTyp
         .collect();
 
     Ok(Some(ValueCell::Object(values)))
-}
\ No newline at end of file
+}
+
+// Compute the CRC-32 checksum of the bytes
+// This is only meant to detect accidental corruption,
+// it must not be used for any security purpose
+fn crc32(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let vec = zelf?.as_bytes()?;
+
+    context.increase_gas_usage(vec.len() as _)?;
+
+    let crc = vec.iter()
+        .fold(u32::MAX, |crc, b| CRC32_TABLE[((crc ^ *b as u32) & 0xFF) as usize] ^ (crc >> 8));
+
+    Ok(Some(Primitive::U32(!crc).into()))
+}
+
+// Decode an unsigned LEB128 integer
+// Returns the value and the number of bytes consumed
+// None if the input is truncated or doesn't fit in a u64
+fn decode_varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate().take(10) {
+        let low = (byte & 0x7F) as u64;
+        // The tenth byte can only hold the last bit of a u64
+        if i == 9 && low > 1 {
+            return None
+        }
+
+        value |= low << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1))
+        }
+    }
+
+    None
+}
+
+// Read a varint at the given offset
+// Returns a tuple (value, bytes consumed) or null if invalid
+fn read_varint(_: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
+    let offset = parameters.remove(1).as_u32()? as usize;
+    let param = parameters.remove(0);
+    let bytes = param.as_ref()?.as_bytes()?;
+
+    let decoded = bytes.get(offset..)
+        .and_then(decode_varint);
+
+    Ok(Some(match decoded {
+        Some((value, consumed)) => ValueCell::Object(vec![
+            Primitive::U64(value).into(),
+            Primitive::U64(consumed as u64).into()
+        ]),
+        None => Primitive::Null.into()
+    }))
+}
+
+// Encode a u64 as an unsigned LEB128 integer
+fn write_varint(_: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
+    let mut value = parameters.remove(0).as_u64()?;
+
+    let mut bytes = Vec::with_capacity(10);
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            break;
+        }
+
+        bytes.push(byte | 0x80);
+    }
+
+    Ok(Some(ValueCell::Bytes(bytes)))
+}
+
+// Take the first bytes of a hash
+// The hash must be exactly 32 bytes long
+fn hash_prefix(_: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let len = parameters.remove(1).as_u32()? as usize;
+    let param = parameters.remove(0);
+    let hash = param.as_ref()?.as_bytes()?;
+
+    if hash.len() != HASH_SIZE {
+        return Err(EnvironmentError::InvalidParameter)
+    }
+
+    if len > HASH_SIZE {
+        return Err(EnvironmentError::OutOfBounds(len, HASH_SIZE))
+    }
+
+    context.increase_gas_usage(len as _)?;
+
+    Ok(Some(ValueCell::Bytes(hash[..len].to_vec())))
+}
+
+fn to_hex(_: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let param = parameters.remove(0);
+    let bytes = param.as_ref()?.as_bytes()?;
+
+    context.increase_gas_usage((bytes.len() * 2) as _)?;
+
+    let hex = bytes.iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+
+    Ok(Some(Primitive::String(hex).into()))
+}
+
+// Returns null on odd length or non hex characters
+fn from_hex(_: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let param = parameters.remove(0);
+    let value = param.as_ref()?.as_string()?;
+
+    context.increase_gas_usage(value.len() as _)?;
+
+    Ok(Some(match decode_hex(value) {
+        Some(bytes) => ValueCell::Bytes(bytes),
+        None => Primitive::Null.into()
+    }))
+}
+
+fn is_valid_utf8(_: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let param = parameters.remove(0);
+    let bytes = param.as_ref()?.as_bytes()?;
+
+    context.increase_gas_usage(bytes.len() as _)?;
+
+    let valid = std::str::from_utf8(bytes).is_ok();
+    Ok(Some(Primitive::Boolean(valid).into()))
+}
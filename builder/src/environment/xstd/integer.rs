@@ -1,4 +1,5 @@
 use tos_environment::{
+    EnvironmentError,
     FnInstance,
     FnParams,
     FnReturnType,
@@ -55,6 +56,43 @@ macro_rules! register_checked_fns {
     };
 }
 
+macro_rules! saturating_fn {
+    ($env: expr, $op: ident, $bound: ident, $t: ident, $f: ident) => {
+        paste! {
+            fn [<saturating_ $op _ $f>](zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
+                let other = parameters.remove(0).into_owned()?.[<as_ $f>]()?;
+                let value = zelf?.[<as_ $f>]()?;
+
+                // Clamp the result at the type bound on overflow
+                let result = value.[<checked_ $op>](other)
+                    .unwrap_or($f::$bound);
+
+                Ok(Some(Primitive::$t(result).into()))
+            }
+
+            $env.register_native_function(
+                stringify!([<saturating_ $op>]),
+                Some(Type::$t),
+                vec![("other", Type::$t)],
+                [<saturating_ $op _ $f>],
+                2,
+                Some(Type::$t)
+            );
+        }
+    };
+}
+
+// macro to register the saturating operations for a specific type
+macro_rules! register_saturating_fns {
+    ($env: expr, $t: ident, $f: ident) => {
+        {
+            saturating_fn!($env, add, MAX, $t, $f);
+            saturating_fn!($env, sub, MIN, $t, $f);
+            saturating_fn!($env, mul, MAX, $t, $f);
+        }
+    };
+}
+
 macro_rules! to_endian_array {
     ($env: expr, $t: ident, $f: ident, $endian: ident) => {
         paste! {
@@ -126,10 +164,10 @@ macro_rules! min {
             $env.register_native_function(
                 "min",
                 Some(Type::$t),
-                vec![],
+                vec![("other", Type::$t)],
                 [<min_ $f>],
                 1,
-                Some(Type::Array(Box::new(Type::U8)))
+                Some(Type::$t)
             );
         }
     };
@@ -150,10 +188,38 @@ macro_rules! max {
             $env.register_native_function(
                 "max",
                 Some(Type::$t),
-                vec![],
+                vec![("other", Type::$t)],
                 [<max_ $f>],
                 1,
-                Some(Type::Array(Box::new(Type::U8)))
+                Some(Type::$t)
+            );
+        }
+    };
+}
+
+macro_rules! clamp {
+    ($env: expr, $t: ident, $f: ident) => {
+        paste! {
+            // Returns an error if min is greater than max
+            fn [<clamp_ $f>](zelf: FnInstance, params: FnParams, _: &mut Context) -> FnReturnType {
+                let value = zelf?.[<as_ $f>]()?;
+                let min = params[0].as_ref()?.[<as_ $f>]()?;
+                let max = params[1].as_ref()?.[<as_ $f>]()?;
+
+                if min > max {
+                    return Err(EnvironmentError::Static("clamp: min is greater than max"))
+                }
+
+                Ok(Some(Primitive::$t(value.clamp(min, max)).into()))
+            }
+
+            $env.register_native_function(
+                "clamp",
+                Some(Type::$t),
+                vec![("min", Type::$t), ("max", Type::$t)],
+                [<clamp_ $f>],
+                1,
+                Some(Type::$t)
             );
         }
     };
@@ -218,4 +284,24 @@ pub fn register(env: &mut EnvironmentBuilder) {
     register_min_max!(env, U64, u64);
     register_min_max!(env, U128, u128);
     register_min_max!(env, U256, u256);
+}
+
+// Register the operations clamping at the type bounds for all types
+pub fn register_saturating(env: &mut EnvironmentBuilder) {
+    register_saturating_fns!(env, U8, u8);
+    register_saturating_fns!(env, U16, u16);
+    register_saturating_fns!(env, U32, u32);
+    register_saturating_fns!(env, U64, u64);
+    register_saturating_fns!(env, U128, u128);
+    register_saturating_fns!(env, U256, u256);
+}
+
+// Register clamp functions for all types
+pub fn register_clamp(env: &mut EnvironmentBuilder) {
+    clamp!(env, U8, u8);
+    clamp!(env, U16, u16);
+    clamp!(env, U32, u32);
+    clamp!(env, U64, u64);
+    clamp!(env, U128, u128);
+    clamp!(env, U256, u256);
 }
\ No newline at end of file
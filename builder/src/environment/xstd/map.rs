@@ -18,6 +18,15 @@ pub fn register(env: &mut EnvironmentBuilder) {
     env.register_native_function("values", Some(_type.clone()), vec![], values, 20, Some(Type::Array(Box::new(value_type.clone()))));
 }
 
+// Combine the entries of two maps
+pub fn register_merge(env: &mut EnvironmentBuilder) {
+    let key_type = Type::T(Some(0));
+    let value_type = Type::T(Some(1));
+    let _type = Type::Map(Box::new(key_type), Box::new(value_type));
+    env.register_native_function("merge", Some(_type.clone()), vec![("other", _type.clone())], merge, 10, Some(_type.clone()));
+    env.register_native_function("extend", Some(_type.clone()), vec![("other", _type.clone())], extend, 10, None);
+}
+
 fn len(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
     let len = zelf?.as_map()?.len();
     Ok(Some(Primitive::U32(len as u32).into()))
@@ -127,6 +136,8 @@ fn clear(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
     Ok(None)
 }
 
+// Keys are returned in insertion order
+// shift_remove keeps the order, swap_remove moves the last entry to the removed slot
 fn keys(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
     let map = zelf?.as_map()?;
 
@@ -140,8 +151,9 @@ fn keys(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
     Ok(Some(ValueCell::Object(keys)))
 }
 
+// Values are returned in the same order as the keys
 fn values(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
-    let map = zelf?.as_mut_map()?;
+    let map = zelf?.as_map()?;
 
     // we need to go through all elements, thus we increase the gas usage
     context.increase_gas_usage((map.len() as u64) * 5)?;
@@ -151,4 +163,63 @@ fn values(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType
         .collect::<Vec<_>>();
 
     Ok(Some(ValueCell::Object(values)))
-}
\ No newline at end of file
+}
+
+// Create a new map with the entries of both maps
+// Entries of the other map override the existing ones
+fn merge(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let param = parameters.remove(0);
+    let other = param.as_ref()?.as_map()?;
+    let map = zelf?.as_map()?;
+
+    // we need to go through all elements of both maps, thus we increase the gas usage
+    context.increase_gas_usage(((map.len() + other.len()) as u64) * 5)?;
+
+    let mut merged = map.clone();
+    for (key, value) in other.iter() {
+        merged.insert(key.clone(), value.clone());
+    }
+
+    if merged.len() > u32::MAX as usize {
+        return Err(EnvironmentError::OutOfMemory)
+    }
+
+    Ok(Some(ValueCell::Map(Box::new(merged))))
+}
+
+// Insert all the entries of the other map in place
+// Entries of the other map override the existing ones
+fn extend(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let param = parameters.remove(0);
+    let other = match param.into_owned()? {
+        ValueCell::Map(other) => other,
+        _ => return Err(EnvironmentError::InvalidType)
+    };
+    let map = zelf?.as_mut_map()?;
+
+    context.increase_gas_usage((other.len() as u64) * 5)?;
+
+    if map.len().saturating_add(other.len()) > u32::MAX as usize {
+        return Err(EnvironmentError::OutOfMemory)
+    }
+
+    for (key, value) in *other {
+        let memory = value.calculate_memory_usage(context.memory_left())?;
+        match map.insert(key, value) {
+            // Only the value is replaced, the key was already accounted
+            Some(previous) => {
+                context.decrease_memory_usage(previous.calculate_memory_usage(usize::MAX)?);
+                context.increase_memory_usage(memory)?;
+            },
+            None => {
+                let key = map.last()
+                    .map(|(key, _)| key.calculate_memory_usage(usize::MAX))
+                    .transpose()?
+                    .unwrap_or_default();
+                context.increase_memory_usage(memory.saturating_add(key))?;
+            }
+        }
+    }
+
+    Ok(None)
+}
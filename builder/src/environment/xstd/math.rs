@@ -76,8 +76,80 @@ macro_rules! register_sqrt_fns {
     };
 }
 
+// Macro to implement mul_div and checked_pow for different integer types
+macro_rules! fixed_point_fns {
+    ($env: expr, $t: ident, $f: ident) => {
+        paste! {
+            // Compute zelf * other / denominator
+            // The product is computed on 256 bits so it can't overflow before the division
+            // Returns null if the denominator is zero or the result doesn't fit in the type
+            fn [<mul_div_ $f>](zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
+                let denominator = parameters.remove(1).into_owned()?.[<as_ $f>]()?;
+                let other = parameters.remove(0).into_owned()?.[<as_ $f>]()?;
+                let value = zelf?.[<as_ $f>]()?;
+
+                let result = u256::from(value)
+                    .checked_mul(u256::from(other))
+                    .and_then(|product| product.checked_div(u256::from(denominator)))
+                    .filter(|result| *result <= u256::from($f::MAX))
+                    .map(|result| Primitive::$t(result.into()));
+
+                Ok(Some(result.unwrap_or_default().into()))
+            }
+
+            // Returns null if the result overflows
+            fn [<checked_pow_ $f>](zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
+                let exp = parameters.remove(0).into_owned()?.as_u32()?;
+                let value = zelf?.[<as_ $f>]()?;
+
+                let result = value.checked_pow(exp)
+                    .map(|v| Primitive::$t(v));
+
+                Ok(Some(result.unwrap_or_default().into()))
+            }
+
+            $env.register_native_function(
+                "mul_div",
+                Some(Type::$t),
+                vec![("other", Type::$t), ("denominator", Type::$t)],
+                [<mul_div_ $f>],
+                10,
+                Some(Type::Optional(Box::new(Type::$t)))
+            );
+
+            $env.register_native_function(
+                "checked_pow",
+                Some(Type::$t),
+                vec![("exp", Type::U32)],
+                [<checked_pow_ $f>],
+                35,
+                Some(Type::Optional(Box::new(Type::$t)))
+            );
+        }
+    };
+}
+
+// Register fixed point functions for numeric types
+// u256 is excluded as its product can't be widened
+macro_rules! register_fixed_point_fns {
+    ($env: expr) => {
+        {
+            fixed_point_fns!($env, U8, u8);
+            fixed_point_fns!($env, U16, u16);
+            fixed_point_fns!($env, U32, u32);
+            fixed_point_fns!($env, U64, u64);
+            fixed_point_fns!($env, U128, u128);
+        }
+    };
+}
+
 // Register all math functions
 pub fn register(env: &mut EnvironmentBuilder) {
     // Register square root functions for all integer types
     register_sqrt_fns!(env);
 }
+
+// Register mul_div and checked_pow functions for all integer types up to u128
+pub fn register_fixed_point(env: &mut EnvironmentBuilder) {
+    register_fixed_point_fns!(env);
+}
@@ -7,7 +7,7 @@ mod map;
 mod bytes;
 mod math;
 
-use std::ptr;
+use std::{cmp::Ordering, ptr};
 
 use tos_types::{Primitive, Type};
 use tos_environment::{
@@ -36,18 +36,56 @@ pub fn register(env: &mut EnvironmentBuilder) {
     env.register_native_function("is_same_ptr", None, vec![("left", Type::Any), ("right", Type::Any)], is_same_ptr, 5, Some(Type::Bool));
     env.register_native_function("require", None, vec![("condition", Type::Bool), ("msg", Type::String)], require, 1, None);
     env.register_native_function("clone", Some(Type::T(None)), vec![], clone, 5, Some(Type::T(None)));
+
+    // Syscall ids are the registration positions and are stored in compiled modules
+    // New natives must be registered after all the existing ones to keep the ids stable
+    register_appended(env);
+
+    // Registered last so the ids of the other natives don't depend on the feature
+    #[cfg(feature = "test-natives")]
+    env.register_native_function("burn_gas", None, vec![("amount", Type::U64)], burn_gas, 1, None);
+}
+
+// Natives added after the initial set, in registration order
+// Only append at the end of this function
+fn register_appended(env: &mut EnvironmentBuilder) {
+    array::register_resize(env);
+    bytes::register_crc32(env);
+    array::register_get_wrapping(env);
+    bytes::register_varint(env);
+    bytes::register_hash_prefix(env);
+    string::register_parse_address(env);
+    env.register_native_function("gas_left", None, vec![], gas_left, 10, Some(Type::U64));
+    math::register_fixed_point(env);
+    integer::register_saturating(env);
+    integer::register_clamp(env);
+    env.register_native_function("require_eq", None, vec![("left", Type::Any), ("right", Type::Any), ("msg", Type::String)], require_eq, 5, None);
+    env.register_native_function("require_gt", None, vec![("left", Type::Any), ("right", Type::Any), ("msg", Type::String)], require_gt, 5, None);
+    env.register_native_function("require_lt", None, vec![("left", Type::Any), ("right", Type::Any), ("msg", Type::String)], require_lt, 5, None);
+    string::register_format(env);
+    bytes::register_hex(env);
+    array::register_sort_dedup(env);
+    array::register_index_of(env);
+    map::register_merge(env);
+    range::register_collect_step(env);
+    string::register_join(env);
+    string::register_parse_int(env);
+    // Raise the gas limit up to the ceiling set by the host
+    env.register_native_function("request_gas", None, vec![("amount", Type::U64)], request_gas, 5, Some(Type::U64));
+    env.register_native_function("log_gas", None, vec![], log_gas, 1, None);
+    bytes::register_is_valid_utf8(env);
 }
 
-fn println(_: FnInstance, parameters: FnParams, _: &mut Context) -> FnReturnType {
+fn println(_: FnInstance, parameters: FnParams, context: &mut Context) -> FnReturnType {
     let param = &parameters[0];
-    println!("{}", param.as_ref()?);
+    context.log(&param.as_ref()?.to_string());
 
     Ok(None)
 }
 
-fn debug(_: FnInstance, parameters: FnParams, _: &mut Context) -> FnReturnType {
+fn debug(_: FnInstance, parameters: FnParams, context: &mut Context) -> FnReturnType {
     let param = &parameters[0];
-    println!("{:?}", param);
+    context.log(&format!("{:?}", param));
 
     Ok(None)
 }
@@ -78,8 +116,10 @@ fn is_same_ptr(_: FnInstance, parameters: FnParams, _: &mut Context) -> FnReturn
     Ok(Some(Primitive::Boolean(same).into()))
 }
 
-fn require(_: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
-    let msg = parameters.remove(1)
+// Read the expect message at the given index
+// Only alphanumeric chars and spaces are allowed
+fn read_expect_msg(parameters: &mut FnParams, index: usize) -> Result<String, EnvironmentError> {
+    let msg = parameters.remove(index)
         .into_owned()?
         .into_string()?;
 
@@ -87,6 +127,12 @@ fn require(_: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturn
         return Err(EnvironmentError::InvalidExpect);
     }
 
+    Ok(msg)
+}
+
+fn require(_: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
+    let msg = read_expect_msg(&mut parameters, 1)?;
+
     let param = &parameters[0];
     let value = param.as_bool()?;
 
@@ -97,6 +143,45 @@ fn require(_: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturn
     }
 }
 
+fn require_eq(_: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
+    let msg = read_expect_msg(&mut parameters, 2)?;
+
+    let left = parameters[0].as_ref()?;
+    let right = parameters[1].as_ref()?;
+
+    if left == right {
+        Ok(None)
+    } else {
+        Err(EnvironmentError::Expect(format!("{}: {:?} != {:?}", msg, left, right)))
+    }
+}
+
+// Both values must be primitives of the same type
+fn require_ordering(mut parameters: FnParams, expected: Ordering, symbol: &str) -> FnReturnType {
+    let msg = read_expect_msg(&mut parameters, 2)?;
+
+    let left = parameters[0].as_ref()?;
+    let right = parameters[1].as_ref()?;
+
+    let ordering = left.as_value()?
+        .partial_cmp(right.as_value()?)
+        .ok_or(EnvironmentError::InvalidType)?;
+
+    if ordering == expected {
+        Ok(None)
+    } else {
+        Err(EnvironmentError::Expect(format!("{}: {:?} {} {:?}", msg, left, symbol, right)))
+    }
+}
+
+fn require_gt(_: FnInstance, parameters: FnParams, _: &mut Context) -> FnReturnType {
+    require_ordering(parameters, Ordering::Greater, "<=")
+}
+
+fn require_lt(_: FnInstance, parameters: FnParams, _: &mut Context) -> FnReturnType {
+    require_ordering(parameters, Ordering::Less, ">=")
+}
+
 fn clone(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
     let zelf = zelf?;
 
@@ -106,4 +191,35 @@ fn clone(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
     context.increase_memory_usage_unchecked(memory)?;
 
     Ok(Some(zelf.clone()))
-}
\ No newline at end of file
+}
+
+// The cost of the call is charged before reading it
+fn gas_left(_: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    Ok(Some(Primitive::U64(context.gas_left()).into()))
+}
+
+// Returns the amount of gas granted
+fn request_gas(_: FnInstance, parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let amount = parameters[0].as_u64()?;
+    let granted = context.request_gas(amount);
+
+    Ok(Some(Primitive::U64(granted).into()))
+}
+
+// Logged through the context log sink like println
+fn log_gas(_: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let msg = format!("gas left: {}", context.gas_left());
+    context.log(&msg);
+
+    Ok(None)
+}
+
+// Consume up to the given amount of gas
+// Errors with NotEnoughGas once the gas left is exhausted
+#[cfg(feature = "test-natives")]
+fn burn_gas(_: FnInstance, parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let amount = parameters[0].as_u64()?;
+    context.burn_gas(amount)?;
+
+    Ok(None)
+}
@@ -1,5 +1,5 @@
 use tos_environment::{Context, EnvironmentError, FnInstance, FnParams, FnReturnType};
-use tos_types::{Type, Primitive, ValueCell};
+use tos_types::{Type, Primitive, ValueCell, U256 as u256};
 use paste::paste;
 
 use crate::EnvironmentBuilder;
@@ -37,6 +37,40 @@ macro_rules! collect {
     };
 }
 
+macro_rules! collect_step {
+    ($t: ident, $f: ident, $start: expr, $end: expr, $step: expr, $context: expr) => {
+        {
+            let (start, end, step) = ($start, $end, $step);
+            if step == $f::default() {
+                return Err(EnvironmentError::InvalidParameter);
+            }
+
+            let descending = start > end;
+            let diff = u256::from(if descending { start - end } else { end - start });
+
+            // Rounded up as the start value is always collected
+            let count = (diff / u256::from(step)) + u256::from(!(diff % u256::from(step)).is_zero());
+            let count = count.as_u64()
+                .filter(|count| *count <= u32::MAX as u64)
+                .ok_or(EnvironmentError::RangeTooLarge)?;
+
+            $context.increase_gas_usage(count * 8)?;
+
+            let mut vec = Vec::with_capacity(count as usize);
+            let mut value = start;
+            for i in 0..count {
+                vec.push(Primitive::$t(value).into());
+                // The value after the last one may not fit in the type
+                if i + 1 < count {
+                    value = if descending { value - step } else { value + step };
+                }
+            }
+
+            ValueCell::Object(vec)
+        }
+    };
+}
+
 macro_rules! count {
     ($t: ident, $start: expr, $end: expr, $type: ident) => {
         paste! {
@@ -57,6 +91,12 @@ pub fn register(env: &mut EnvironmentBuilder) {
     env.register_native_function("count", Some(_type.clone()), vec![], count, 5, Some(Type::T(Some(0))));
 }
 
+// Collect a range with a step, in descending order if start is greater than end
+pub fn register_collect_step(env: &mut EnvironmentBuilder) {
+    let _type = Type::Range(Box::new(Type::T(Some(0))));
+    env.register_native_function("collect_step", Some(_type), vec![("step", Type::T(Some(0)))], collect_step, 20, Some(Type::Array(Box::new(Type::T(Some(0))))));
+}
+
 fn contains(zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
     let value = parameters.remove(0);
     let zelf = zelf?;
@@ -106,6 +146,24 @@ fn collect(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType
     }))
 }
 
+// Collect the values from start to end (exclusive) with the given step
+// If start is greater than end, the values are collected in descending order
+fn collect_step(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let param = parameters.remove(0);
+    let step = param.as_ref()?.as_value()?;
+    let zelf = zelf?;
+    let (start, end) = zelf.as_range()?;
+    Ok(Some(match (start, end, step) {
+        (Primitive::U8(start), Primitive::U8(end), Primitive::U8(step)) => collect_step!(U8, u8, *start, *end, *step, context),
+        (Primitive::U16(start), Primitive::U16(end), Primitive::U16(step)) => collect_step!(U16, u16, *start, *end, *step, context),
+        (Primitive::U32(start), Primitive::U32(end), Primitive::U32(step)) => collect_step!(U32, u32, *start, *end, *step, context),
+        (Primitive::U64(start), Primitive::U64(end), Primitive::U64(step)) => collect_step!(U64, u64, *start, *end, *step, context),
+        (Primitive::U128(start), Primitive::U128(end), Primitive::U128(step)) => collect_step!(U128, u128, *start, *end, *step, context),
+        (Primitive::U256(start), Primitive::U256(end), Primitive::U256(step)) => collect_step!(U256, u256, *start, *end, *step, context),
+        _ => return Err(EnvironmentError::InvalidType)
+    }))
+}
+
 fn max(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
     let zelf = zelf?;
     let (_, end) = zelf.as_range()?;
@@ -1,4 +1,4 @@
-use tos_environment::Context;
+use tos_environment::{Context, EnvironmentError};
 use tos_types::{Type, Primitive, ValueCell};
 use super::{
     FnInstance,
@@ -7,6 +7,13 @@ use super::{
     EnvironmentBuilder
 };
 
+// Size of an address in bytes
+const ADDRESS_SIZE: usize = 32;
+// Longest base58 encoding of a 32 bytes address
+const ADDRESS_BASE58_MAX_LEN: usize = 44;
+// Bitcoin base58 alphabet
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
 pub fn register(env: &mut EnvironmentBuilder) {
     // String
     env.register_native_function("len", Some(Type::String), vec![], len, 1, Some(Type::U32));
@@ -30,14 +37,37 @@ pub fn register(env: &mut EnvironmentBuilder) {
     env.register_native_function("substring_range", Some(Type::String), vec![("value", Type::U32), ("value", Type::U32)], string_substring_range, 3, Some(Type::Optional(Box::new(Type::String))));
 }
 
+// Parse a 32 bytes address from its hex or base58 representation
+pub fn register_parse_address(env: &mut EnvironmentBuilder) {
+    env.register_native_function("parse_address", None, vec![("value", Type::String)], parse_address, 5, Some(Type::Optional(Box::new(Type::Bytes))));
+}
+
+// Replace each {} placeholder of the template by the next value
+pub fn register_format(env: &mut EnvironmentBuilder) {
+    env.register_native_function("format", None, vec![("template", Type::String), ("values", Type::Array(Box::new(Type::Any)))], format, 5, Some(Type::String));
+}
+
+// Concatenate an array of strings with a separator
+pub fn register_join(env: &mut EnvironmentBuilder) {
+    env.register_native_function("join", Some(Type::Array(Box::new(Type::String))), vec![("separator", Type::String)], join, 5, Some(Type::String));
+}
+
+// Parse a base 10 unsigned integer
+pub fn register_parse_int(env: &mut EnvironmentBuilder) {
+    env.register_native_function("parse_int", None, vec![("value", Type::String)], parse_int, 1, Some(Type::Optional(Box::new(Type::U64))));
+}
+
 fn len(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
     let s = zelf?.as_string()?;
     Ok(Some(Primitive::U32(s.len() as u32).into()))
 }
 
-fn trim(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
-    let s = zelf?.as_string()?.trim().to_string();
-    Ok(Some(Primitive::String(s).into()))
+// Remove the leading and trailing Unicode whitespaces
+fn trim(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let s = zelf?.as_string()?;
+    context.increase_gas_usage(s.len() as _)?;
+
+    Ok(Some(Primitive::String(s.trim().to_string()).into()))
 }
 
 fn contains(zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
@@ -56,14 +86,20 @@ fn contains_ignore_case(zelf: FnInstance, mut parameters: FnParams, _: &mut Cont
     Ok(Some(Primitive::Boolean(s.contains(&value)).into()))
 }
 
-fn to_uppercase(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
-    let s: String = zelf?.as_string()?.to_uppercase();
-    Ok(Some(Primitive::String(s).into()))
+// Unicode case mapping, the result may not have the same byte length
+fn to_uppercase(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let s = zelf?.as_string()?;
+    context.increase_gas_usage(s.len() as _)?;
+
+    Ok(Some(Primitive::String(s.to_uppercase()).into()))
 }
 
-fn to_lowercase(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
-    let s: String = zelf?.as_string()?.to_lowercase();
-    Ok(Some(Primitive::String(s).into()))
+// Unicode case mapping, the result may not have the same byte length
+fn to_lowercase(zelf: FnInstance, _: FnParams, context: &mut Context) -> FnReturnType {
+    let s = zelf?.as_string()?;
+    context.increase_gas_usage(s.len() as _)?;
+
+    Ok(Some(Primitive::String(s.to_lowercase()).into()))
 }
 
 fn to_bytes(zelf: FnInstance, _: FnParams, _: &mut Context) -> FnReturnType {
@@ -131,11 +167,18 @@ fn ends_with(zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnR
     Ok(Some(Primitive::Boolean(s.ends_with(value)).into()))
 }
 
-fn split(zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
+// Returns an error if the separator is empty
+fn split(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
     let s = zelf?.as_string()?;
     let param = parameters.remove(0);
     let handle = param.as_ref()?;
     let value = handle.as_string()?;
+    if value.is_empty() {
+        return Err(EnvironmentError::InvalidParameter)
+    }
+
+    context.increase_gas_usage(s.len() as _)?;
+
     let values = s.split(value)
         .map(|s| Primitive::String(s.to_string()).into())
         .collect();
@@ -143,6 +186,24 @@ fn split(zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnRetur
     Ok(Some(ValueCell::Object(values)))
 }
 
+fn join(zelf: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let values = zelf?.as_vec()?;
+    let param = parameters.remove(0);
+    let separator = param.as_ref()?.as_string()?;
+
+    let parts = values.iter()
+        .map(|v| v.as_string())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let len = parts.iter()
+        .map(|v| v.len())
+        .sum::<usize>()
+        .saturating_add(separator.len().saturating_mul(parts.len().saturating_sub(1)));
+    context.increase_gas_usage(len as _)?;
+
+    Ok(Some(Primitive::String(parts.join(separator)).into()))
+}
+
 fn char_at(zelf: FnInstance, mut parameters: FnParams, _: &mut Context) -> FnReturnType {
     let param =  parameters.remove(0);
     let index = param.as_u32()? as usize;
@@ -193,4 +254,116 @@ fn string_substring_range(zelf: FnInstance, mut parameters: FnParams, _: &mut Co
     } else {
         Ok(Some(Primitive::Null.into()))
     }
-}
\ No newline at end of file
+}
+
+// Decode an hex string, None if it contains a non hex character
+pub(super) fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None
+    }
+
+    value.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            Some((high * 16 + low) as u8)
+        })
+        .collect()
+}
+
+// Decode a base58 string, None if it contains a character outside the alphabet
+fn decode_base58(value: &str) -> Option<Vec<u8>> {
+    // Little endian accumulator of the decoded number
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in value.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|v| *v == c)? as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    // Each leading '1' encodes a leading zero byte
+    let zeros = value.bytes().take_while(|c| *c == b'1').count();
+    bytes.extend(std::iter::repeat_n(0, zeros));
+    bytes.reverse();
+
+    Some(bytes)
+}
+
+// Hex is detected by its length (64 chars), otherwise base58 is expected
+// Returns null if the value isn't a valid 32 bytes address
+fn parse_address(_: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let param = parameters.remove(0);
+    let value = param.as_ref()?.as_string()?;
+
+    let decoded = if value.len() == ADDRESS_SIZE * 2 {
+        context.increase_gas_usage(value.len() as _)?;
+        decode_hex(value)
+    } else if value.len() <= ADDRESS_BASE58_MAX_LEN {
+        // base58 decoding is quadratic in the input length
+        context.increase_gas_usage((value.len() * value.len()) as _)?;
+        decode_base58(value)
+    } else {
+        None
+    };
+
+    Ok(Some(match decoded {
+        Some(bytes) if bytes.len() == ADDRESS_SIZE => ValueCell::Bytes(bytes),
+        _ => Primitive::Null.into()
+    }))
+}
+
+// Whitespaces are not trimmed, any non digit character returns None
+// An overflowing value returns None
+fn parse_int(_: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let param = parameters.remove(0);
+    let value = param.as_ref()?.as_string()?;
+    context.increase_gas_usage(value.len() as _)?;
+
+    let parsed = if value.bytes().all(|b| b.is_ascii_digit()) {
+        value.parse::<u64>().ok()
+    } else {
+        None
+    };
+
+    Ok(Some(parsed.map(Primitive::U64).unwrap_or_default().into()))
+}
+
+// Returns an error if the placeholders count doesn't match the values count
+fn format(_: FnInstance, mut parameters: FnParams, context: &mut Context) -> FnReturnType {
+    let values_param = parameters.remove(1);
+    let values = values_param.as_ref()?.as_vec()?;
+    let template_param = parameters.remove(0);
+    let template = template_param.as_ref()?.as_string()?;
+
+    let parts = template.split("{}").collect::<Vec<_>>();
+    if parts.len() - 1 != values.len() {
+        return Err(EnvironmentError::Static("format: placeholders count doesn't match values count"))
+    }
+
+    // Charge the output as it grows so a huge result fails before being built
+    context.increase_gas_usage(template.len() as _)?;
+
+    let mut output = String::with_capacity(template.len());
+    for (part, value) in parts.iter().zip(values.iter()) {
+        output.push_str(part);
+
+        let value = value.to_string();
+        context.increase_gas_usage(value.len() as _)?;
+        output.push_str(&value);
+    }
+
+    if let Some(last) = parts.last() {
+        output.push_str(last);
+    }
+
+    Ok(Some(Primitive::String(output).into()))
+}
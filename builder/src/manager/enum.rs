@@ -15,11 +15,8 @@ impl EnumBuilder {
     }
 
     pub fn get_variant_by_name(&self, name: &str) -> Option<(u8, &EnumVariant)> {
-        self.inner.variants()
-            .iter()
-            .enumerate()
-            .find(|(_, (k, _))| k == name)
-            .map(|(index, (_, v))| (index as u8, v))
+        let id = self.inner.variant_id_by_name(name)?;
+        self.get_variant_by_id(id).map(|(_, variant)| (id, variant))
     }
 
     pub fn variants(&self) -> &Vec<(Cow<'static, str>, EnumVariant)> {
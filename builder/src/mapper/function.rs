@@ -135,7 +135,9 @@ impl<'a> FunctionMapper<'a> {
                 }
             }
 
+            // Only generic parameters are resolved against the instance type
             let mut cast_to_type = on_type
+                .filter(|_| a.is_generic())
                 .map(Type::get_inner_type)
                 .filter(|t| b.is_castable_to(t));
 
@@ -28,6 +28,12 @@ impl Hasher for TypeIdHasher {
     }
 }
 
+// Callback invoked with the gas left once it drops below a threshold
+pub type GasWarningFn<'r> = Box<dyn FnMut(u64) + 'r>;
+
+// Callback receiving the messages logged by the program
+pub type LogSinkFn<'r> = Box<dyn FnMut(&str) + 'r>;
+
 // Context is a simple data store that allows for storing and retrieving values of different types.
 pub struct Context<'ty, 'r> {
     data: HashMap<TypeId, Data<'ty, 'r>, BuildHasherDefault<TypeIdHasher>>,
@@ -35,6 +41,9 @@ pub struct Context<'ty, 'r> {
     // By default, set to u64::MAX because
     // no program should be able to run indefinitely
     max_gas: u64,
+    // Ceiling up to which the program can raise the gas limit
+    // By default, the gas limit can't be raised
+    gas_ceiling: Option<u64>,
     // Price per byte of memory
     memory_price_per_byte: u64,
     // Max value depth allowed
@@ -46,6 +55,15 @@ pub struct Context<'ty, 'r> {
     current_gas: u64,
     // Current memory used in the execution
     current_memory: usize,
+    // Threshold and callback to call when the gas left
+    // drops below it for the first time
+    gas_warning: Option<(u64, GasWarningFn<'r>)>,
+    // Whether the gas warning can still be triggered
+    // Re-armed when the usage is reset
+    gas_warning_armed: bool,
+    // Destination of the logged messages
+    // If not set, they are printed to the stdout
+    log_sink: Option<LogSinkFn<'r>>,
 }
 
 impl Default for Context<'_, '_> {
@@ -60,11 +78,15 @@ impl<'ty, 'r> Context<'ty, 'r> {
         Self {
             data: HashMap::default(),
             max_gas: u64::MAX,
+            gas_ceiling: None,
             current_gas: 0,
             memory_price_per_byte: 1,
             max_value_depth: 16,
             max_memory_usage: 1024 * 1024 * 128, // 128 MB
             current_memory: 0,
+            gas_warning: None,
+            gas_warning_armed: false,
+            log_sink: None,
         }
     }
 
@@ -74,6 +96,24 @@ impl<'ty, 'r> Context<'ty, 'r> {
         self.max_gas = gas;
     }
 
+    // Set the ceiling up to which the program can raise the gas limit
+    #[inline(always)]
+    pub fn set_gas_ceiling(&mut self, ceiling: u64) {
+        self.gas_ceiling = Some(ceiling);
+    }
+
+    // Raise the gas limit by up to `gas` units without going above the ceiling
+    // Returns the amount granted, 0 if no ceiling is set
+    pub fn request_gas(&mut self, gas: u64) -> u64 {
+        let available = self.gas_ceiling
+            .map_or(0, |ceiling| ceiling.saturating_sub(self.max_gas));
+
+        let granted = gas.min(available);
+        self.max_gas += granted;
+
+        granted
+    }
+
     // Set the price per byte of memory
     #[inline(always)]
     pub fn set_memory_price_per_byte(&mut self, price: u64) {
@@ -92,6 +132,12 @@ impl<'ty, 'r> Context<'ty, 'r> {
         self.current_gas
     }
 
+    // Get the gas left before reaching the limit
+    #[inline(always)]
+    pub fn gas_left(&self) -> u64 {
+        self.max_gas.saturating_sub(self.current_gas)
+    }
+
     // Get the max value depth allowed
     #[inline(always)]
     pub fn max_value_depth(&self) -> usize {
@@ -105,12 +151,58 @@ impl<'ty, 'r> Context<'ty, 'r> {
         self.max_value_depth = depth;
     }
 
+    // Set a callback to be called once when the gas left drops below the threshold
+    // It is called again after the usage is reset with reset_usage
+    // This is host-side only and doesn't affect the execution
+    #[inline(always)]
+    pub fn set_gas_warning(&mut self, threshold: u64, callback: GasWarningFn<'r>) {
+        self.gas_warning = Some((threshold, callback));
+        self.gas_warning_armed = true;
+    }
+
+    // Set the destination of the logged messages
+    // This allows the host to collect them instead of printing them
+    #[inline(always)]
+    pub fn set_log_sink(&mut self, sink: LogSinkFn<'r>) {
+        self.log_sink = Some(sink);
+    }
+
+    // Log a message to the sink, or print it if no sink is set
+    #[inline]
+    pub fn log(&mut self, message: &str) {
+        match self.log_sink.as_mut() {
+            Some(sink) => sink(message),
+            None => println!("{}", message)
+        }
+    }
+
+    // Call the gas warning callback if the threshold has been crossed
+    // It is disarmed so it is only called once per execution
+    #[inline]
+    fn check_gas_warning(&mut self) {
+        if !self.gas_warning_armed {
+            return;
+        }
+
+        let left = self.gas_left();
+        if let Some((threshold, callback)) = self.gas_warning.as_mut() {
+            if left < *threshold {
+                self.gas_warning_armed = false;
+                callback(left);
+            }
+        }
+    }
+
     // Increase the gas usage by a specific amount
     #[inline]
     pub fn increase_gas_usage(&mut self, gas: u64) -> Result<(), EnvironmentError> {
         self.current_gas = self.current_gas.checked_add(gas)
             .ok_or(EnvironmentError::GasOverflow)?;
 
+        // Checked before any error so the warning is also
+        // triggered when a single charge exhausts the gas
+        self.check_gas_warning();
+
         if self.current_gas > self.max_gas {
             return Err(EnvironmentError::NotEnoughGas { limit: self.max_gas, actual: self.current_gas });
         }
@@ -118,6 +210,25 @@ impl<'ty, 'r> Context<'ty, 'r> {
         Ok(())
     }
 
+    // Burn up to `gas` units, stopping at the gas limit
+    // If the requested amount exceeds the gas left, all remaining
+    // gas is consumed and a NotEnoughGas error is returned
+    // This is mostly useful to test out of gas handling paths
+    pub fn burn_gas(&mut self, gas: u64) -> Result<(), EnvironmentError> {
+        let left = self.gas_left();
+        if gas > left {
+            let actual = self.current_gas.saturating_add(gas);
+            self.current_gas = self.max_gas;
+            self.check_gas_warning();
+            return Err(EnvironmentError::NotEnoughGas { limit: self.max_gas, actual });
+        }
+
+        self.current_gas += gas;
+        self.check_gas_warning();
+
+        Ok(())
+    }
+
     // Get the current memory usage
     #[inline(always)]
     pub fn current_memory_usage(&self) -> usize {
@@ -264,10 +375,12 @@ impl<'ty, 'r> Context<'ty, 'r> {
     }
 
     // Reset the gas & memory usage
+    // This also re-arms the gas warning
     #[inline]
     pub fn reset_usage(&mut self) {
         self.current_gas = 0;
         self.current_memory = 0;
+        self.gas_warning_armed = self.gas_warning.is_some();
     }
 }
 
@@ -325,6 +438,71 @@ mod tests {
         assert_eq!(dummy.0, "Hello, World!");
     }
 
+    #[test]
+    fn test_context_burn_gas() {
+        let mut context = Context::new();
+        context.set_gas_limit(100);
+
+        assert!(context.burn_gas(60).is_ok());
+        assert_eq!(context.gas_left(), 40);
+
+        assert!(matches!(
+            context.burn_gas(50),
+            Err(EnvironmentError::NotEnoughGas { limit: 100, actual: 110 })
+        ));
+        assert_eq!(context.gas_left(), 0);
+        assert_eq!(context.current_gas_usage(), 100);
+
+        // Nothing left to burn
+        assert!(context.burn_gas(1).is_err());
+        assert!(context.burn_gas(0).is_ok());
+    }
+
+    #[test]
+    fn test_context_gas_warning() {
+        use std::cell::RefCell;
+
+        let calls = RefCell::new(Vec::new());
+        let mut context = Context::new();
+        context.set_gas_limit(100);
+        context.set_gas_warning(30, Box::new(|left| calls.borrow_mut().push(left)));
+
+        context.increase_gas_usage(50).unwrap();
+        assert!(calls.borrow().is_empty());
+
+        context.increase_gas_usage(25).unwrap();
+        context.increase_gas_usage(10).unwrap();
+        context.burn_gas(5).unwrap();
+
+        // Re-armed once the usage is reset
+        context.reset_usage();
+        context.increase_gas_usage(80).unwrap();
+
+        // Triggered even if the charge exhausts the gas
+        context.reset_usage();
+        assert!(context.increase_gas_usage(150).is_err());
+        context.reset_usage();
+        assert!(context.burn_gas(150).is_err());
+
+        drop(context);
+        assert_eq!(calls.into_inner(), vec![25, 20, 0, 0]);
+    }
+
+    #[test]
+    fn test_context_log_sink() {
+        use std::cell::RefCell;
+
+        let logs = RefCell::new(Vec::new());
+        let mut context = Context::new();
+        context.set_log_sink(Box::new(|msg| logs.borrow_mut().push(msg.to_owned())));
+
+        context.log("hello");
+        context.log("world");
+
+        drop(context);
+        assert_eq!(logs.into_inner(), vec!["hello", "world"]);
+    }
+
     #[test]
     fn test_downcast_to_trait_any() {
         trait Foo {
@@ -401,4 +579,23 @@ mod tests {
 
         inner_mut_fn::<Dummy>(&mut context);
     }
+
+    #[test]
+    fn test_context_request_gas() {
+        let mut context = Context::new();
+        context.set_gas_limit(100);
+
+        // No ceiling, nothing is granted
+        assert_eq!(context.request_gas(50), 0);
+        assert_eq!(context.gas_left(), 100);
+
+        context.set_gas_ceiling(150);
+        assert_eq!(context.request_gas(30), 30);
+        assert_eq!(context.gas_left(), 130);
+
+        // Capped at the ceiling
+        assert_eq!(context.request_gas(100), 20);
+        assert_eq!(context.gas_left(), 150);
+        assert_eq!(context.request_gas(1), 0);
+    }
 }
\ No newline at end of file
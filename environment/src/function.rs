@@ -14,6 +14,8 @@ pub type OnCallFn = fn(FnInstance, FnParams, &mut Context) -> FnReturnType;
 // This is used to register functions in the environment
 #[derive(Debug, Clone)]
 pub struct NativeFunction {
+    // name of the function
+    name: String,
     // function on type
     on_type: Option<Type>,
     require_instance: bool,
@@ -28,8 +30,9 @@ pub struct NativeFunction {
 impl NativeFunction {
     // Create a new instance of the NativeFunction
     #[inline]
-    pub fn new(on_type: Option<Type>, require_instance: bool, parameters: Vec<Type>, on_call: OnCallFn, cost: u64, return_type: Option<Type>) -> Self {
+    pub fn new(name: String, on_type: Option<Type>, require_instance: bool, parameters: Vec<Type>, on_call: OnCallFn, cost: u64, return_type: Option<Type>) -> Self {
         Self {
+            name,
             on_type,
             require_instance,
             parameters,
@@ -39,6 +42,12 @@ impl NativeFunction {
         }
     }
 
+    // Get the function name
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     // Get function on type
     // example: Foo::bar
     // Foo is on_type
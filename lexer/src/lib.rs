@@ -48,7 +48,7 @@ pub struct Lexer<'a> {
     // characters in the input
     // used to build tokens
     chars: VecDeque<char>,
-    // current position index in the input
+    // current byte position in the input
     // this is used to get slices from it
     pos: usize,
     // current line number we are reading
@@ -88,9 +88,7 @@ impl<'a> Lexer<'a> {
 
     // advance by n characters
     fn advance_by(&mut self, n: usize) -> Result<(), LexerError> {
-        let drain = self.chars.drain(0..n);
-
-        if drain.len() != n {
+        if self.chars.len() < n {
             return Err(LexerError {
                 line: self.line,
                 column: self.column,
@@ -98,7 +96,10 @@ impl<'a> Lexer<'a> {
             });
         }
 
-        self.pos += n;
+        // pos is a byte offset in the input
+        self.pos += self.chars.drain(0..n)
+            .map(char::len_utf8)
+            .sum::<usize>();
         self.column += n;
 
         Ok(())
@@ -117,7 +118,7 @@ impl<'a> Lexer<'a> {
     // get the next character
     fn next_char(&mut self) -> Option<char> {
         self.chars.pop_front().map(|c| {
-            self.pos += 1;
+            self.pos += c.len_utf8();
             self.column += 1;
             c
         })
@@ -135,7 +136,7 @@ impl<'a> Lexer<'a> {
 
     // push a character back to the list
     fn push_back(&mut self, c: char) {
-        self.pos -= 1;
+        self.pos -= c.len_utf8();
         self.column -= 1;
         self.chars.push_front(c);
     }
@@ -256,7 +257,7 @@ impl<'a> Lexer<'a> {
                 // skip the escaped character
             } else {
                 if escape {
-                    init_pos = self.pos - 1;
+                    init_pos = self.pos - c.len_utf8();
                     escape = false;
                 }
             }
@@ -612,6 +613,16 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_multi_byte_string() {
+        let code = "\"été ß\"";
+        let lexer = Lexer::new(code);
+        let tokens = lexer.get().unwrap();
+        assert_eq!(tokens, vec![
+            Token::Value(Literal::String(Cow::Borrowed("été ß")))
+        ]);
+    }
+
     #[test]
     fn test_inner_string() {
         let code = "\"'Hello, World!'\"";
@@ -4243,6 +4243,31 @@ mod tests {
         assert_eq!(statements.len(), 1);
     }
 
+    #[test]
+    fn test_non_generic_param_not_cast_to_instance() {
+        let mut env = EnvironmentBuilder::new();
+        env.register_native_function("foo", Some(Type::U64), vec![("n", Type::U32)], |_, _, _| { Ok(None) }, 0, None);
+
+        // a.foo(5u32)
+        let tokens = vec![
+            Token::Identifier("a"),
+            Token::Dot,
+            Token::Identifier("foo"),
+            Token::ParenthesisOpen,
+            Token::Value(Literal::U32(5)),
+            Token::ParenthesisClose
+        ];
+
+        let statements = test_parser_statement_with(tokens, vec![("a", Type::U64)], &None, &env);
+        assert_eq!(statements.len(), 1);
+
+        // The constant must keep the parameter type and not the instance one
+        let Statement::Expression(Expression::FunctionCall(_, _, params)) = &statements[0] else {
+            panic!("expected a function call");
+        };
+        assert_eq!(params, &vec![Expression::Constant(Primitive::U32(5).into())]);
+    }
+
     #[test]
     fn test_type_t_same_as_instance() {
         // let a: optional<u64> = 10;
@@ -2,9 +2,12 @@ use std::{borrow::Cow, hash::{Hash, Hasher}, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 
-use crate::IdentifierType;
+use crate::{IdentifierType, ValueError};
 use super::Type;
 
+// Maximum number of variants in an enum
+pub const MAX_ENUM_VARIANTS: usize = u8::MAX as usize;
+
 // Represents a variant of an enum
 // This is similar to a struct
 #[derive(Clone, Hash, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -20,6 +23,21 @@ impl EnumVariant {
     pub fn fields(&self) -> &[(Cow<'static, str>, Type)] {
         &self.0
     }
+
+    // Get the index of a field by its name
+    // If the name is duplicated, the first field is returned
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.0.iter()
+            .position(|(field_name, _)| field_name == name)
+    }
+
+    // Get the type of a field by its name
+    // If the name is duplicated, the first field is returned
+    pub fn field_type(&self, name: &str) -> Option<&Type> {
+        self.0.iter()
+            .find(|(field_name, _)| field_name == name)
+            .map(|(_, field_type)| field_type)
+    }
 }
 
 impl From<Vec<(Cow<'static, str>, Type)>> for EnumVariant {
@@ -69,8 +87,20 @@ pub struct EnumValueType {
 
 impl EnumType {
     // Create a new enum type
+    // Panics if there is more than MAX_ENUM_VARIANTS variants
     pub fn new(id: IdentifierType, name: impl Into<Cow<'static, str>>, variants: Vec<(Cow<'static, str>, EnumVariant)>) -> Self {
-        Self(Arc::new(Enum { id, name: name.into(), variants }))
+        Self::try_new(id, name, variants)
+            .expect("enum variants count exceeds the u8 limit")
+    }
+
+    // Create a new enum type
+    // Returns an error if there is more than MAX_ENUM_VARIANTS variants
+    pub fn try_new(id: IdentifierType, name: impl Into<Cow<'static, str>>, variants: Vec<(Cow<'static, str>, EnumVariant)>) -> Result<Self, ValueError> {
+        if variants.len() > MAX_ENUM_VARIANTS {
+            return Err(ValueError::TooManyEnumVariants(variants.len(), MAX_ENUM_VARIANTS));
+        }
+
+        Ok(Self(Arc::new(Enum { id, name: name.into(), variants })))
     }
 
     // Get the unique identifier of the enum
@@ -96,6 +126,19 @@ impl EnumType {
     pub fn get_variant(&self, id: u8) -> Option<&(Cow<'static, str>, EnumVariant)> {
         self.0.variants.get(id as usize)
     }
+
+    // Get the id of a variant by its name
+    pub fn variant_id_by_name(&self, name: &str) -> Option<u8> {
+        self.0.variants.iter()
+            .position(|(variant_name, _)| variant_name == name)
+            .map(|id| id as u8)
+    }
+
+    // Get a variant by its name
+    pub fn get_variant_by_name(&self, name: &str) -> Option<&(Cow<'static, str>, EnumVariant)> {
+        self.0.variants.iter()
+            .find(|(variant_name, _)| variant_name == name)
+    }
 }
 
 impl EnumValueType {
@@ -131,6 +174,99 @@ impl Serialize for EnumType {
 
 impl<'a> Deserialize<'a> for EnumType {
     fn deserialize<D: serde::Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
-        Ok(Self(Arc::new(Enum::deserialize(deserializer)?)))
+        let inner = Enum::deserialize(deserializer)?;
+        if inner.variants.len() > MAX_ENUM_VARIANTS {
+            return Err(serde::de::Error::custom(ValueError::TooManyEnumVariants(inner.variants.len(), MAX_ENUM_VARIANTS)));
+        }
+
+        Ok(Self(Arc::new(inner)))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color_enum() -> EnumType {
+        EnumType::new(0, "Color", vec![
+            (Cow::Borrowed("Red"), EnumVariant::new(vec![])),
+            (Cow::Borrowed("Green"), EnumVariant::new(vec![])),
+            (Cow::Borrowed("Custom"), vec![("r", Type::U8), ("g", Type::U8), ("b", Type::U8)].into()),
+        ])
+    }
+
+    #[test]
+    fn test_variant_by_name() {
+        let color = color_enum();
+        assert_eq!(color.variant_id_by_name("Red"), Some(0));
+        assert_eq!(color.variant_id_by_name("Custom"), Some(2));
+        assert_eq!(color.variant_id_by_name("Blue"), None);
+        assert_eq!(color.variant_id_by_name("red"), None);
+
+        let (name, variant) = color.get_variant_by_name("Custom").unwrap();
+        assert_eq!(name, "Custom");
+        assert_eq!(variant.fields().len(), 3);
+        assert!(color.get_variant_by_name("Blue").is_none());
+    }
+
+    #[test]
+    fn test_variant_field_by_name() {
+        let color = color_enum();
+        let (_, variant) = color.get_variant(2).unwrap();
+        assert_eq!(variant.field_index("g"), Some(1));
+        assert_eq!(variant.field_type("b"), Some(&Type::U8));
+        assert_eq!(variant.field_index("a"), None);
+        assert_eq!(variant.field_type("a"), None);
+
+        let (_, variant) = color.get_variant(0).unwrap();
+        assert_eq!(variant.field_index("r"), None);
+
+        // First match wins on duplicated names
+        let variant: EnumVariant = vec![("value", Type::U8), ("value", Type::String)].into();
+        assert_eq!(variant.field_index("value"), Some(0));
+        assert_eq!(variant.field_type("value"), Some(&Type::U8));
+    }
+
+    #[test]
+    fn test_variants_limit() {
+        let variants = |count: usize| (0..count)
+            .map(|i| (Cow::Owned(format!("V{}", i)), EnumVariant::new(vec![])))
+            .collect::<Vec<_>>();
+
+        let max = EnumType::try_new(0, "Max", variants(MAX_ENUM_VARIANTS)).unwrap();
+        assert_eq!(max.variants().len(), 255);
+        assert_eq!(max.variant_id_by_name("V254"), Some(254));
+
+        assert!(matches!(
+            EnumType::try_new(0, "TooMany", variants(MAX_ENUM_VARIANTS + 1)),
+            Err(ValueError::TooManyEnumVariants(256, 255))
+        ));
+    }
+
+    #[test]
+    fn test_deserialize_variants_limit() {
+        let variants = |count: usize| (0..count)
+            .map(|i| (Cow::Owned(format!("V{}", i)), EnumVariant::new(vec![])))
+            .collect::<Vec<_>>();
+
+        let max = EnumType::try_new(0, "Max", variants(MAX_ENUM_VARIANTS)).unwrap();
+        let json = serde_json::to_value(&max).unwrap();
+        assert_eq!(serde_json::from_value::<EnumType>(json).unwrap(), max);
+
+        // Built directly as it can't be created through EnumType
+        let too_many = Enum { id: 0, name: "TooMany".into(), variants: variants(MAX_ENUM_VARIANTS + 1) };
+        let json = serde_json::to_value(&too_many).unwrap();
+        let err = serde_json::from_value::<EnumType>(json).unwrap_err();
+        assert_eq!(err.to_string(), ValueError::TooManyEnumVariants(256, 255).to_string());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_variants_limit_panics() {
+        let variants = (0..=MAX_ENUM_VARIANTS)
+            .map(|i| (Cow::Owned(format!("V{}", i)), EnumVariant::new(vec![])))
+            .collect();
+
+        EnumType::new(0, "TooMany", variants);
+    }
+}
@@ -546,7 +546,7 @@ impl Shl<u32> for U256 {
 
         for i in (word_shift..4).rev() {
             result[i] = self.0[i - word_shift] << bit_shift;
-            if bit_shift > 0 && i > 0 {
+            if bit_shift > 0 && i > word_shift {
                 result[i] |= self.0[i - word_shift - 1] >> (64 - bit_shift);
             }
         }
@@ -598,7 +598,7 @@ impl Shl for U256 {
         let word_shift = word_shift as usize;
         for i in (word_shift..4).rev() {
             result[i] = self.0[i - word_shift] << bit_shift;
-            if bit_shift > 0 && i > 0 {
+            if bit_shift > 0 && i > word_shift {
                 result[i] |= self.0[i - word_shift - 1] >> (64 - bit_shift);
             }
         }
@@ -934,6 +934,11 @@ mod tests {
 
         let a = U256([1, 0, 0, 0]);
         assert_eq!(a.shl(128), U256([0, 0, 1, 0]));
+
+        // Shift across words with a bit offset
+        let a = U256([u64::MAX, 0, 0, 0]);
+        assert_eq!(a.shl(65), U256([0, u64::MAX - 1, 1, 0]));
+        assert_eq!(a.shl(U256::from(65u64)), U256([0, u64::MAX - 1, 1, 0]));
     }
 
     #[test]
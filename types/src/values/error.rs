@@ -46,4 +46,6 @@ pub enum ValueError {
     UnknownType,
     #[error("max memory reached: {0}/{1} bytes")]
     MaxMemoryReached(usize, usize),
+    #[error("too many enum variants: {0}/{1}")]
+    TooManyEnumVariants(usize, usize),
 }
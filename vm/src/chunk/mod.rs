@@ -14,6 +14,8 @@ const REGISTERS_SIZE: usize = u16::MAX as usize;
 // Manager for a chunk
 // It contains the reader and the stacks
 pub struct ChunkManager<'a> {
+    // Id of the chunk in its module
+    chunk_id: u16,
     reader: ChunkReader<'a>,
     // Registers are temporary and "scoped" per chunk
     registers: Vec<StackValue>,
@@ -26,14 +28,21 @@ impl<'a> ChunkManager<'a> {
     // It will create a reader from the chunk
     // and initialize the stack and registers
     #[inline]
-    pub fn new(chunk: &'a Chunk) -> Self {
+    pub fn new(chunk_id: u16, chunk: &'a Chunk) -> Self {
         ChunkManager {
+            chunk_id,
             reader: ChunkReader::new(chunk),
             registers: Vec::new(),
             iterators: Vec::new(),
         }
     }
 
+    // Get the id of the chunk in its module
+    #[inline]
+    pub fn chunk_id(&self) -> u16 {
+        self.chunk_id
+    }
+
     // Get the registers
     #[inline]
     pub fn get_registers(&self) -> &Vec<StackValue> {
@@ -104,6 +104,14 @@ impl<'a> ChunkReader<'a> {
         Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
     }
 
+    // Read a u16 from the instructions without advancing
+    #[cfg(feature = "gas-profile")]
+    #[inline]
+    pub fn peek_u16(&self) -> Option<u16> {
+        self.chunk.get_instructions_at(self.ip, 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
     // Read a u32 from the instructions
     #[inline]
     pub fn read_u32(&mut self) -> Result<u32, VMError> {
@@ -82,6 +82,8 @@ pub enum VMError {
     Static(&'static str),
     #[error("Error, memory not cleaned, we have {0} bytes left")]
     MemoryNotCleaned(usize),
+    #[error("instruction limit exceeded: {0}")]
+    InstructionLimitExceeded(u64),
 }
 
 impl From<EnvironmentError> for VMError {
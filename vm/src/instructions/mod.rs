@@ -153,6 +153,12 @@ impl<'a> InstructionTable<'a> {
         self.instructions[opcode.as_usize()] = handler;
     }
 
+    // Get the cost of an instruction
+    #[inline(always)]
+    pub fn instruction_cost(&self, opcode: OpCode) -> u64 {
+        self.instructions[opcode.as_usize()].1
+    }
+
     // Allow to overwrite the cost of an instruction
     pub fn set_instruction_cost(&mut self, opcode: OpCode, cost: u64) {
         self.instructions[opcode.as_usize()].1 = cost;
@@ -5,11 +5,16 @@ mod stack;
 mod validator;
 mod instructions;
 
+#[cfg(feature = "trace")]
+mod trace;
+
 #[cfg(test)]
 mod tests;
 
 use stack::Stack;
 use log::trace;
+#[cfg(feature = "trace")]
+use std::collections::VecDeque;
 
 // Re-export the necessary types
 pub use tos_environment::*;
@@ -21,10 +26,20 @@ pub use instructions::*;
 pub use error::VMError;
 pub use chunk::*;
 
+#[cfg(feature = "trace")]
+pub use trace::TraceEntry;
+#[cfg(feature = "gas-profile")]
+pub use trace::{GasProfiler, GasSegment, SyscallStats};
+
 // 64 elements maximum in the call stack
 // This represents how many calls can be chained
 const CALL_STACK_SIZE: usize = 64;
 
+// Default number of trace entries kept per run
+// Each entry copies the registers, so the trace must stay bounded
+#[cfg(feature = "trace")]
+pub const DEFAULT_TRACE_LIMIT: usize = 4096;
+
 // 8 modules maximum in the stack
 // This represents how many modules can be chained
 const MODULES_STACK_SIZE: usize = 8;
@@ -68,7 +83,21 @@ pub struct VM<'a, 'r> {
     context: Context<'a, 'r>,
     // Flag to enable/disable the tail call optimization
     // in our VM
-    tail_call_optimization: bool
+    tail_call_optimization: bool,
+    // Max instructions allowed per run
+    // This is independent of the gas limit
+    max_instructions: Option<u64>,
+    // Instructions executed during the last run
+    executed_instructions: u64,
+    // Last instructions executed during the last run
+    // Only recorded when the tracing is enabled
+    // Oldest entries are dropped once the limit is reached
+    #[cfg(feature = "trace")]
+    trace: Option<(VecDeque<TraceEntry>, usize)>,
+    // Gas consumed per basic block during the last run
+    // Only recorded when the gas profiling is enabled
+    #[cfg(feature = "gas-profile")]
+    gas_profiler: Option<GasProfiler>
 }
 
 impl<'a, 'r> VM<'a, 'r> {
@@ -93,7 +122,13 @@ impl<'a, 'r> VM<'a, 'r> {
             call_stack_size: 0,
             stack: Stack::new(),
             context,
-            tail_call_optimization: false
+            tail_call_optimization: false,
+            max_instructions: None,
+            executed_instructions: 0,
+            #[cfg(feature = "trace")]
+            trace: None,
+            #[cfg(feature = "gas-profile")]
+            gas_profiler: None
         }
     }
 
@@ -109,6 +144,83 @@ impl<'a, 'r> VM<'a, 'r> {
         self.tail_call_optimization = value;
     }
 
+    // Set the max instructions allowed per run
+    // None means no limit, only the gas limit applies
+    #[inline(always)]
+    pub fn set_max_instructions(&mut self, max: Option<u64>) {
+        self.max_instructions = max;
+    }
+
+    // Get the max instructions allowed per run
+    #[inline(always)]
+    pub fn max_instructions(&self) -> Option<u64> {
+        self.max_instructions
+    }
+
+    // Get the instructions count executed during the last run
+    #[inline(always)]
+    pub fn executed_instructions(&self) -> u64 {
+        self.executed_instructions
+    }
+
+    // Enable the instruction tracing
+    // The last DEFAULT_TRACE_LIMIT instructions executed by the next runs will be recorded
+    #[cfg(feature = "trace")]
+    #[inline(always)]
+    pub fn enable_trace(&mut self) {
+        self.enable_trace_with_limit(DEFAULT_TRACE_LIMIT);
+    }
+
+    // Enable the instruction tracing
+    // Only the last `limit` instructions executed by the next runs will be recorded
+    #[cfg(feature = "trace")]
+    #[inline(always)]
+    pub fn enable_trace_with_limit(&mut self, limit: usize) {
+        self.trace = Some((VecDeque::new(), limit));
+    }
+
+    // Disable the instruction tracing and drop the recorded trace
+    #[cfg(feature = "trace")]
+    #[inline(always)]
+    pub fn disable_trace(&mut self) {
+        self.trace = None;
+    }
+
+    // Get the last instructions executed during the last run, oldest first
+    // Empty if the tracing is disabled
+    #[cfg(feature = "trace")]
+    #[inline(always)]
+    pub fn last_trace(&self) -> impl DoubleEndedIterator<Item = &TraceEntry> {
+        self.trace.iter().flat_map(|(entries, _)| entries.iter())
+    }
+
+    // Enable the gas profiling
+    // The gas consumed by each basic block of the next runs will be recorded
+    #[cfg(feature = "gas-profile")]
+    #[inline(always)]
+    pub fn enable_gas_profile(&mut self) {
+        self.gas_profiler = Some(GasProfiler::default());
+    }
+
+    // Get the gas consumed per basic block during the last run
+    #[cfg(feature = "gas-profile")]
+    #[inline(always)]
+    pub fn gas_profile(&self) -> Vec<&GasSegment> {
+        self.gas_profiler.as_ref()
+            .map(|profiler| profiler.segments().collect())
+            .unwrap_or_default()
+    }
+
+    // Get the calls count and gas consumed per syscall during the last run
+    // Returned in first call order
+    #[cfg(feature = "gas-profile")]
+    #[inline(always)]
+    pub fn syscall_stats(&self) -> Vec<&SyscallStats> {
+        self.gas_profiler.as_ref()
+            .map(|profiler| profiler.syscalls().collect())
+            .unwrap_or_default()
+    }
+
     // Get the stack
     #[inline(always)]
     pub fn get_stack(&self) -> &Stack {
@@ -155,7 +267,7 @@ impl<'a, 'r> VM<'a, 'r> {
             .and_then(|module| module.get_chunk_at(id as usize))
             .ok_or(VMError::ChunkNotFound)?;
 
-        let manager = ChunkManager::new(chunk);
+        let manager = ChunkManager::new(id, chunk);
         self.stack.mark_checkpoint();
         self.call_stack.push(Some(manager));
         self.call_stack_size += 1;
@@ -263,12 +375,76 @@ impl<'a, 'r> VM<'a, 'r> {
     // It will execute the bytecode
     // First chunk executed should always return a value
     pub fn run(&mut self) -> Result<ValueCell, VMError> {
+        self.executed_instructions = 0;
+
+        #[cfg(feature = "trace")]
+        if let Some((entries, _)) = self.trace.as_mut() {
+            entries.clear();
+        }
+
+        #[cfg(feature = "gas-profile")]
+        if let Some(profiler) = self.gas_profiler.as_mut() {
+            profiler.clear();
+        }
+
+        let result = self.run_modules();
+
+        // Flushed on every exit so the block that failed is also recorded
+        #[cfg(feature = "gas-profile")]
+        if let Some(profiler) = self.gas_profiler.as_mut() {
+            profiler.flush();
+        }
+
+        result
+    }
+
+    // Execute all the modules injected until their call stacks are empty
+    fn run_modules(&mut self) -> Result<ValueCell, VMError> {
         // Freely copy the module has its a reference only
         // We go through every modules injected
         'modules: while let Some(module) = self.backend.modules.last().copied() {
             'call_stack: while let Some(Some(mut manager)) = self.call_stack.pop() {
                 while let Some(opcode) = manager.next_u8() {
-                    match self.backend.table.execute(opcode, &self.backend, &mut self.stack, &mut manager, &mut self.context) {
+                    if let Some(max) = self.max_instructions {
+                        if self.executed_instructions >= max {
+                            return Err(VMError::InstructionLimitExceeded(max));
+                        }
+                    }
+                    self.executed_instructions += 1;
+
+                    #[cfg(feature = "trace")]
+                    if let Some((entries, limit)) = self.trace.as_mut() {
+                        if *limit > 0 {
+                            if entries.len() >= *limit {
+                                entries.pop_front();
+                            }
+                            entries.push_back(TraceEntry::new(manager.index() - 1, opcode, manager.get_registers()));
+                        }
+                    }
+
+                    #[cfg(feature = "gas-profile")]
+                    let (ip, gas_before) = (manager.index() - 1, self.context.current_gas_usage());
+                    #[cfg(feature = "gas-profile")]
+                    let syscall = if opcode == OpCode::SysCall.as_byte() {
+                        manager.peek_u16()
+                    } else {
+                        None
+                    };
+
+                    let result = self.backend.table.execute(opcode, &self.backend, &mut self.stack, &mut manager, &mut self.context);
+
+                    #[cfg(feature = "gas-profile")]
+                    if let Some(profiler) = self.gas_profiler.as_mut() {
+                        let gas = self.context.current_gas_usage().saturating_sub(gas_before);
+                        profiler.record(self.backend.modules.len() - 1, manager.chunk_id(), ip, opcode, gas);
+                        if let Some(id) = syscall {
+                            if let Some(function) = self.backend.environment.get_functions().get(id as usize) {
+                                profiler.record_syscall(id, function.name(), gas);
+                            }
+                        }
+                    }
+
+                    match result {
                         Ok(InstructionResult::Nothing) => {},
                         Ok(InstructionResult::InvokeChunk(id)) => {
                             if module.is_entry_chunk(id as usize) {
@@ -73,6 +73,323 @@ fn test_max_gas() {
     assert!(matches!(vm.run(), Err(VMError::EnvironmentError(EnvironmentError::NotEnoughGas { .. }))));
 }
 
+#[test]
+fn test_gas_left() {
+    let code = r#"
+        entry main() {
+            let first: u64 = gas_left();
+            let second: u64 = gas_left();
+            assert(second < first);
+
+            // Stop the loop before running out of gas
+            let i: u64 = 0;
+            while gas_left() > 500 {
+                i += 1;
+            }
+            return i
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+    vm.context_mut().set_gas_limit(10_000);
+
+    let iterations = vm.run().unwrap().into_value().unwrap().as_u64().unwrap();
+    assert!(iterations > 0);
+    assert!(vm.context().gas_left() > 0);
+}
+
+#[test]
+fn test_request_gas() {
+    let code = r#"
+        entry main() {
+            let granted: u64 = request_gas(5000);
+            return granted + request_gas(100000)
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+    vm.context_mut().set_gas_limit(10_000);
+    vm.context_mut().set_gas_ceiling(50_000);
+
+    // The second request is capped at the ceiling
+    assert_eq!(vm.run().unwrap().into_value().unwrap(), Primitive::U64(40_000));
+    assert!(vm.context().gas_left() > 49_000);
+
+    // Without a ceiling, nothing is granted
+    let mut vm = VM::new(&environment);
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+    vm.context_mut().set_gas_limit(10_000);
+
+    assert_eq!(vm.run().unwrap().into_value().unwrap(), Primitive::U64(0));
+}
+
+#[test]
+fn test_log_gas() {
+    let gas_used = |call: &str| {
+        let code = format!(r#"
+            entry main() {{
+                let before: u64 = gas_left();
+                {}
+                let after: u64 = gas_left();
+                return before - after
+            }}
+        "#, call);
+
+        let (module, environment) = prepare_module(&code);
+        let mut vm = VM::new(&environment);
+        vm.append_module(&module).expect("module");
+        vm.invoke_entry_chunk(0).expect("valid entry chunk");
+        vm.context_mut().set_gas_limit(10_000);
+        vm.run().unwrap().into_value().unwrap().as_u64().unwrap()
+    };
+
+    // The log_gas native costs 1 on top of the syscall instruction
+    let syscall_cost = InstructionTable::new().instruction_cost(OpCode::SysCall);
+    assert_eq!(gas_used("log_gas();") - gas_used(""), syscall_cost + 1);
+}
+
+#[test]
+fn test_log_sink() {
+    use std::cell::RefCell;
+
+    let code = r#"
+        entry main() {
+            println("hello");
+            println(42);
+            log_gas();
+            return 0
+        }
+    "#;
+
+    let logs = RefCell::new(Vec::new());
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+    vm.context_mut().set_log_sink(Box::new(|msg| logs.borrow_mut().push(msg.to_owned())));
+
+    vm.run().unwrap();
+    drop(vm);
+    let logs = logs.into_inner();
+    assert_eq!(logs.len(), 3);
+    assert_eq!(logs[..2], ["hello", "42"]);
+    assert!(logs[2].starts_with("gas left: "));
+}
+
+#[test]
+fn test_arithmetic_instruction_costs() {
+    let gas_used = |op: &str| {
+        let code = format!(r#"
+            entry main() {{
+                let x: u64 = 1000000;
+                for i: u64 = 0; i < 100; i += 1 {{
+                    x {} 3;
+                }}
+                return x
+            }}
+        "#, op);
+
+        let (module, environment) = prepare_module(&code);
+        let mut vm = VM::new(&environment);
+        vm.append_module(&module).expect("module");
+        vm.invoke_entry_chunk(0).expect("valid entry chunk");
+        vm.run().unwrap();
+        vm.context().current_gas_usage()
+    };
+
+    let table = InstructionTable::new();
+    let add_cost = table.instruction_cost(OpCode::AssignAdd);
+    let div_cost = table.instruction_cost(OpCode::AssignDiv);
+    assert!(div_cost > add_cost);
+
+    // Same instruction count, only the arithmetic opcode differs
+    assert_eq!(gas_used("/=") - gas_used("+="), 100 * (div_cost - add_cost));
+}
+
+#[test]
+fn test_max_instructions() {
+    let code = r#"
+        entry main() {
+            let i: u64 = 0;
+            while i < 1000 {
+                i += 1;
+            }
+            return i
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+    vm.context_mut().set_gas_limit(1_000_000);
+    vm.set_max_instructions(Some(100));
+
+    assert!(matches!(vm.run(), Err(VMError::InstructionLimitExceeded(100))));
+    assert_eq!(vm.executed_instructions(), 100);
+    assert!(vm.context().gas_left() > 0);
+
+    // Without the limit, only the gas applies
+    let mut vm = VM::new(&environment);
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+    vm.context_mut().set_gas_limit(1_000_000);
+
+    assert_eq!(vm.run().unwrap().into_value().unwrap(), Primitive::U64(1000));
+    assert!(vm.executed_instructions() > 100);
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn test_trace() {
+    let code = r#"
+        entry main() {
+            let x: u64 = 10;
+            let values: u64[] = [x];
+            return values[0] + 5
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.enable_trace();
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+
+    assert_eq!(vm.run().unwrap().into_value().unwrap(), Primitive::U64(15));
+
+    let trace = vm.last_trace().collect::<Vec<_>>();
+    assert!(!trace.is_empty());
+    assert!(matches!(trace.last().and_then(|e| e.op_code()), Some(OpCode::Return)));
+    // x is stored in the first register
+    assert!(trace.iter().any(|e| e.registers().first() == Some(&Some(Primitive::U64(10)))));
+    // the array is not copied in the snapshot
+    assert!(trace.iter().any(|e| e.registers().len() == 2 && e.registers()[1].is_none()));
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn test_trace_limit() {
+    let code = r#"
+        entry main() {
+            let x: u64 = 0;
+            for i: u64 = 0; i < 100; i += 1 {
+                x += i;
+            }
+            return x
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.enable_trace_with_limit(16);
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+
+    assert_eq!(vm.run().unwrap().into_value().unwrap(), Primitive::U64(4950));
+
+    // Only the last instructions are kept
+    assert_eq!(vm.last_trace().count(), 16);
+    assert!(matches!(vm.last_trace().last().and_then(|e| e.op_code()), Some(OpCode::Return)));
+}
+
+#[test]
+fn test_burn_gas() {
+    let code = r#"
+        entry main() {
+            burn_gas(1000);
+            burn_gas(u64::MAX);
+            return 0
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+    vm.context_mut().set_gas_limit(10_000);
+
+    assert!(matches!(
+        vm.run(),
+        Err(VMError::EnvironmentError(EnvironmentError::NotEnoughGas { limit: 10_000, .. }))
+    ));
+    assert_eq!(vm.context().gas_left(), 0);
+    assert_eq!(vm.context().current_gas_usage(), 10_000);
+}
+
+#[test]
+#[cfg(feature = "gas-profile")]
+fn test_gas_profile() {
+    let code = r#"
+        entry main() {
+            let total: u64 = 0;
+            for i: u64 = 0; i < 100; i += 1 {
+                total += i;
+            }
+            return total
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.enable_gas_profile();
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+
+    assert_eq!(vm.run().unwrap().into_value().unwrap(), Primitive::U64(4950));
+
+    let profile = vm.gas_profile();
+    let setup = profile.iter()
+        .find(|s| s.start() == 0)
+        .expect("setup segment");
+    let hottest = profile.iter()
+        .max_by_key(|s| s.gas())
+        .expect("hottest segment");
+
+    assert!(hottest.start() > setup.end());
+    assert!(hottest.hits() >= 100);
+    assert!(hottest.gas() > setup.gas());
+    assert_eq!(profile.iter().map(|s| s.gas()).sum::<u64>(), vm.context().current_gas_usage());
+}
+
+#[test]
+#[cfg(feature = "gas-profile")]
+fn test_gas_profile_out_of_gas() {
+    let code = r#"
+        entry main() {
+            let total: u64 = 0;
+            while true {
+                total += 1;
+            }
+            return total
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+
+    // Run out of gas at different points of the loop body
+    for limit in 1000..1010 {
+        let mut vm = VM::new(&environment);
+        vm.enable_gas_profile();
+        vm.append_module(&module).expect("module");
+        vm.invoke_entry_chunk(0).expect("valid entry chunk");
+        vm.context_mut().set_gas_limit(limit);
+
+        assert!(matches!(vm.run(), Err(VMError::EnvironmentError(EnvironmentError::NotEnoughGas { .. }))));
+
+        // The block that ran out of gas is recorded too
+        let profile = vm.gas_profile();
+        assert_eq!(profile.iter().map(|s| s.gas()).sum::<u64>(), vm.context().current_gas_usage());
+    }
+}
+
 #[test]
 fn test_pow() {
     let code = r#"
@@ -1888,4 +2205,1066 @@ fn test_function_assign() {
     "#;
 
     assert_eq!(run_code_id(code, 2), Primitive::U64(10));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_bytes_array_conversion() {
+    let code = r#"
+        entry main() {
+            let values: u8[] = [1, 2, 3, 255];
+            let data: bytes = values.to_bytes();
+            assert(data.len() == 4);
+            assert(data.last().unwrap() == 255);
+
+            let back: u8[] = data.to_array();
+            assert(back.len() == 4);
+            return back[0] as u64 + back[3] as u64
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(256));
+
+    // Only u8 arrays can be converted to bytes
+    let code = r#"
+        entry main() {
+            let values: u64[] = [1, 256];
+            let _: bytes = values.to_bytes();
+            return 0
+        }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(code).collect::<Result<_, _>>().unwrap();
+    let env = EnvironmentBuilder::default();
+    assert!(Parser::with(tokens.into_iter(), &env).parse().is_err());
+}
+
+#[test]
+fn test_array_resize() {
+    let code = r#"
+        entry main() {
+            let values: u64[] = [1, 2, 3, 4];
+            values.resize(2, 0);
+            assert(values.len() == 2);
+            assert(values[1] == 2);
+
+            values.resize(5, 7);
+            assert(values.len() == 5);
+            assert(values[1] == 2);
+            assert(values[4] == 7);
+
+            values.resize(5, 9);
+            assert(values.len() == 5);
+            assert(values[4] == 7);
+
+            return values[0] + values[2]
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(8));
+
+    let code = r#"
+        entry main() {
+            let values: u64[] = [1, 2];
+            values.resize(4, "a");
+            return 0
+        }
+    "#;
+
+    let tokens: Vec<_> = Lexer::new(code).collect::<Result<_, _>>().unwrap();
+    let env = EnvironmentBuilder::default();
+    assert!(Parser::with(tokens.into_iter(), &env).parse().is_err());
+}
+
+#[test]
+fn test_bytes_crc32() {
+    let code = r#"
+        entry main() {
+            let data: bytes = "123456789".to_bytes().to_bytes();
+            return data.crc32() as u64
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0xCBF43926));
+
+    let code = r#"
+        entry main() {
+            let data: bytes = bytes::new();
+            return data.crc32() as u64
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+
+    let code = r#"
+        entry main() {
+            let data: bytes = "The quick brown fox jumps over the lazy dog".to_bytes().to_bytes();
+            return data.crc32() as u64
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0x414FA339));
+}
+
+#[test]
+fn test_array_get_wrapping() {
+    let code = r#"
+        entry main() {
+            let values: u64[] = [10, 20, 30];
+            assert(values.get_wrapping(1) == 20);
+            assert(values.get_wrapping(3) == 10);
+            assert(values.get_wrapping(7) == 20);
+            return values.get_wrapping(4000000001)
+        }
+    "#;
+
+    // 4000000001 % 3 == 2
+    assert_eq!(run_code(code), Primitive::U64(30));
+
+    let code = r#"
+        entry main() {
+            let values: u64[] = [];
+            return values.get_wrapping(0)
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::OutOfBounds(0, 0)))
+        )
+    );
+}
+
+#[test]
+fn test_varint() {
+    let code = r#"
+        fn roundtrip(value: u64) -> u64 {
+            let data: bytes = write_varint(value);
+            let (decoded, consumed): (u64, u64) = read_varint(data, 0).unwrap();
+            assert(decoded == value);
+            assert(consumed == data.len() as u64);
+            return consumed
+        }
+
+        entry main() {
+            assert(roundtrip(0) == 1);
+            assert(roundtrip(127) == 1);
+            assert(roundtrip(300) == 2);
+            assert(roundtrip(18446744073709551615) == 10);
+
+            let data: bytes = write_varint(300);
+            assert(data.first().unwrap() == 172);
+            assert(data.last().unwrap() == 2);
+
+            // Read from an offset
+            let values: u8[] = [255, 172, 2];
+            let (value, consumed): (u64, u64) = read_varint(values.to_bytes(), 1).unwrap();
+            assert(consumed == 2);
+            return value
+        }
+    "#;
+
+    assert_eq!(run_code_id(code, 1), Primitive::U64(300));
+
+    let code = r#"
+        entry main() {
+            // Truncated: continuation bit set on the last byte
+            let truncated: u8[] = [172];
+            assert(read_varint(truncated.to_bytes(), 0).is_none());
+            assert(read_varint(truncated.to_bytes(), 5).is_none());
+
+            // Overflow: too many continuation bytes
+            let overflow: u8[] = [255, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1];
+            assert(read_varint(overflow.to_bytes(), 0).is_none());
+
+            // Overflow: tenth byte holds more than the last bit
+            let overflow: u8[] = [255, 255, 255, 255, 255, 255, 255, 255, 255, 2];
+            assert(read_varint(overflow.to_bytes(), 0).is_none());
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+fn test_hash_prefix() {
+    let code = r#"
+        fn hash() -> bytes {
+            let data: bytes = bytes::new();
+            for i: u8 = 0; i < 32; i += 1 {
+                data.push(i);
+            }
+            return data
+        }
+
+        entry main() {
+            let short: bytes = hash_prefix(hash(), 4);
+            assert(short.len() == 4);
+            assert(short.last().unwrap() == 3);
+
+            assert(hash_prefix(hash(), 8).len() == 8);
+            assert(hash_prefix(hash(), 0).len() == 0);
+
+            let full: bytes = hash_prefix(hash(), 32);
+            assert(full.len() == 32);
+            assert(full.last().unwrap() == 31);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code_id(code, 1), Primitive::U64(0));
+
+    let code = r#"
+        fn hash() -> bytes {
+            let data: bytes = bytes::new();
+            for i: u8 = 0; i < 32; i += 1 {
+                data.push(i);
+            }
+            return data
+        }
+
+        entry main() {
+            let _: bytes = hash_prefix(hash(), 33);
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 1),
+            Err(VMError::EnvironmentError(EnvironmentError::OutOfBounds(33, 32)))
+        )
+    );
+
+    let code = r#"
+        entry main() {
+            let _: bytes = hash_prefix(bytes::new(), 4);
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::InvalidParameter))
+        )
+    );
+}
+
+#[test]
+fn test_parse_address() {
+    let code = r#"
+        entry main() {
+            let hex: bytes = parse_address("0102030405060708090a0B0C0D0E0F101112131415161718191a1b1c1d1e1f20").unwrap();
+            assert(hex.len() == 32);
+            assert(hex.first().unwrap() == 1);
+            assert(hex.last().unwrap() == 32);
+
+            let base58: bytes = parse_address("4wBqpZM9xaSheZzJSMawUKKwhdpChKbZ5eu5ky4Vigw").unwrap();
+            assert(base58 == hex);
+
+            // Leading '1' are zero bytes
+            let zero: bytes = parse_address("11111111111111111111111111111111").unwrap();
+            assert(zero.len() == 32);
+            assert(zero.last().unwrap() == 0);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+
+    let code = r#"
+        entry main() {
+            // Wrong length hex
+            assert(parse_address("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f").is_none());
+            assert(parse_address("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f2021").is_none());
+            // Invalid characters
+            assert(parse_address("zz02030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20").is_none());
+            assert(parse_address("0wBqpZM9xaSheZzJSMawUKKwhdpChKbZ5eu5ky4Vigw").is_none());
+            // Valid base58 but not 32 bytes
+            assert(parse_address("4wBqpZM9").is_none());
+            assert(parse_address("").is_none());
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+#[cfg(feature = "gas-profile")]
+fn test_syscall_stats() {
+    let code = r#"
+        entry main() {
+            let values: u64[] = [];
+            for i: u64 = 0; i < 10; i += 1 {
+                values.push(i);
+            }
+            return values.len() as u64
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.enable_gas_profile();
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+
+    assert_eq!(vm.run().unwrap().into_value().unwrap(), Primitive::U64(10));
+
+    let stats = vm.syscall_stats();
+    assert_eq!(stats.len(), 2);
+    // push is called first, then len
+    let (push, len) = (stats[0], stats[1]);
+    assert_eq!(push.name(), "push");
+    assert_eq!(len.name(), "len");
+    assert_ne!(push.id(), len.id());
+    assert_eq!(push.calls(), 10);
+    assert_eq!(len.calls(), 1);
+    let function = &environment.get_functions()[push.id() as usize];
+    assert!(push.gas() >= function.get_cost() * 10);
+}
+
+#[test]
+fn test_mul_div() {
+    let code = r#"
+        entry main() {
+            // (u128::MAX / 2) * 2 overflows before the division
+            let half: u128 = u128::MAX / 2;
+            assert(half.mul_div(2, 2).unwrap() == half);
+            assert(u128::MAX.mul_div(u128::MAX, u128::MAX).unwrap() == u128::MAX);
+
+            // 1.5 * 2.25 with 6 decimals
+            let scale: u128 = 1000000;
+            assert(1500000u128.mul_div(2250000, scale).unwrap() == 3375000);
+
+            // Result doesn't fit or zero denominator
+            assert(u128::MAX.mul_div(2, 1).is_none());
+            assert(10u128.mul_div(10, 0).is_none());
+            assert(200u8.mul_div(200, 100).is_none());
+            assert(200u8.mul_div(200, 250).unwrap() == 160);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+fn test_checked_pow() {
+    let code = r#"
+        entry main() {
+            assert(10u128.checked_pow(38).unwrap() == 10u128 ** 38u32);
+            assert(10u128.checked_pow(39).is_none());
+            assert(2u8.checked_pow(7).unwrap() == 128);
+            assert(2u8.checked_pow(8).is_none());
+            assert(0u64.checked_pow(0).unwrap() == 1);
+
+            // Non constant exponent
+            let exp: u32 = 3;
+            assert(2u64.checked_pow(exp).unwrap() == 8);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+fn test_saturating_arithmetic() {
+    let code = r#"
+        entry main() {
+            // Within bounds, same as the checked variants
+            assert(200u8.saturating_add(50) == 250);
+            assert(200u8.checked_add(50).unwrap() == 250);
+            assert(10u64.saturating_sub(3) == 7);
+            assert(10u64.saturating_mul(3) == 30);
+
+            // Clamped at the bounds
+            assert(200u8.saturating_add(100) == u8::MAX);
+            assert(200u8.checked_add(100).is_none());
+            assert(3u32.saturating_sub(5) == 0);
+            assert(3u32.checked_sub(5).is_none());
+            assert(u64::MAX.saturating_mul(2) == u64::MAX);
+            assert(u64::MAX.checked_mul(2).is_none());
+            assert(u128::MAX.saturating_add(1) == u128::MAX);
+            assert(u256::MAX.saturating_add(1) == u256::MAX);
+            assert(0u256.saturating_sub(1) == 0);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+fn test_min_max_clamp() {
+    let code = r#"
+        entry main() {
+            assert(3u8.min(5) == 3);
+            assert(3u8.max(5) == 5);
+            assert(7u8.clamp(1, 5) == 5);
+            assert(300u16.min(200) == 200);
+            assert(300u16.clamp(400, 500) == 400);
+            assert(10u32.max(u32::MAX) == u32::MAX);
+            assert(10u32.clamp(0, 20) == 10);
+            assert(10u64.min(0) == 0);
+            assert(10u64.clamp(10, 10) == 10);
+            assert(u128::MAX.clamp(0, 1) == 1);
+            assert(5u256.max(6) == 6);
+            assert(5u256.clamp(1, 3) == 3);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+
+    let code = r#"
+        entry main() {
+            return 5u64.clamp(10, 1)
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::Static(_)))
+        )
+    );
+}
+
+#[test]
+fn test_require_comparisons() {
+    let code = r#"
+        entry main() {
+            let a: u64 = 10;
+            require_eq(a, 10, "a must be ten");
+            require_eq("hello", "hello", "same strings");
+            require_gt(a, 5, "a must be greater");
+            require_lt(a, 20, "a must be lower");
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+
+    let code = r#"
+        entry main() {
+            let a: u64 = 10;
+            require_eq(a, 42, "invalid value");
+            return 0
+        }
+    "#;
+
+    match try_run_code(code, 0) {
+        Err(VMError::EnvironmentError(EnvironmentError::Expect(msg))) => {
+            assert_eq!(msg, "invalid value: Default(U64(10)) != Default(U64(42))");
+        },
+        res => panic!("unexpected result: {:?}", res)
+    };
+
+    let code = r#"
+        entry main() {
+            let a: u64 = 10;
+            require_gt(a, 10, "too low");
+            return 0
+        }
+    "#;
+
+    match try_run_code(code, 0) {
+        Err(VMError::EnvironmentError(EnvironmentError::Expect(msg))) => {
+            assert_eq!(msg, "too low: Default(U64(10)) <= Default(U64(10))");
+        },
+        res => panic!("unexpected result: {:?}", res)
+    };
+
+    // The message must still be alphanumeric
+    let code = r#"
+        entry main() {
+            require_lt(1, 2, "invalid!");
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::InvalidExpect))
+        )
+    );
+}
+
+#[test]
+fn test_format() {
+    let code = r#"
+        entry main() {
+            let values: u64[] = [1, 2, 3];
+            let s: string = format("{} + {} = {}", values);
+            assert(s == "1 + 2 = 3");
+
+            let names: string[] = ["alice", "bob"];
+            assert(format("from {} to {}!", names) == "from alice to bob!");
+            assert(format("{}{}", names) == "alicebob");
+
+            let empty: u64[] = [];
+            assert(format("no placeholder", empty) == "no placeholder");
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+
+    let code = r#"
+        entry main() {
+            let values: u64[] = [1, 2];
+            let _: string = format("only {}", values);
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::Static(_)))
+        )
+    );
+
+    // The output is charged per byte, 4000 bytes here
+    let values = format!(r#"["{0}", "{0}", "{0}", "{0}"]"#, "a".repeat(1000));
+    let gas_usage = |call: &str, limit: u64| {
+        let code = format!(r#"
+            entry main() {{
+                let values: string[] = {};
+                {}
+                return 0
+            }}
+        "#, values, call);
+
+        let (module, environment) = prepare_module(&code);
+        let mut vm = VM::new(&environment);
+        vm.append_module(&module).expect("module");
+        vm.invoke_entry_chunk(0).expect("valid entry chunk");
+        vm.context_mut().set_gas_limit(limit);
+        vm.run().map(|_| vm.context().current_gas_usage())
+    };
+
+    let base = gas_usage("", u64::MAX).unwrap();
+    let call = r#"let _: string = format("{}{}{}{}", values);"#;
+    assert!(gas_usage(call, u64::MAX).unwrap() >= base + 4000);
+    assert!(matches!(
+        gas_usage(call, base + 2000),
+        Err(VMError::EnvironmentError(EnvironmentError::NotEnoughGas { .. }))
+    ));
+}
+
+#[test]
+fn test_hex() {
+    let code = r#"
+        entry main() {
+            let values: u8[] = [0, 1, 171, 255];
+            let data: bytes = values.to_bytes();
+            let hex: string = to_hex(data);
+            assert(hex == "0001abff");
+            assert(from_hex(hex).unwrap() == data);
+            assert(from_hex("0001ABFF").unwrap() == data);
+            assert(from_hex("0001AbfF").unwrap() == data);
+            assert(to_hex(bytes::new()) == "");
+            assert(from_hex("").unwrap().len() == 0);
+
+            // Invalid inputs
+            assert(from_hex("abc").is_none());
+            assert(from_hex("zz").is_none());
+            assert(from_hex("0x00").is_none());
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+fn test_is_valid_utf8() {
+    let code = r#"
+        entry main() {
+            assert(is_valid_utf8(bytes::new()));
+            assert(is_valid_utf8("hello".to_bytes().to_bytes()));
+            // Multi-byte characters
+            assert(is_valid_utf8("été ß".to_bytes().to_bytes()));
+            assert(is_valid_utf8(from_hex("f09f9880").unwrap()));
+
+            // Invalid byte
+            assert(!is_valid_utf8(from_hex("ff").unwrap()));
+            // Truncated sequence
+            assert(!is_valid_utf8(from_hex("68c3").unwrap()));
+            // Overlong encoding
+            assert(!is_valid_utf8(from_hex("c0af").unwrap()));
+            // Surrogate
+            assert(!is_valid_utf8(from_hex("eda080").unwrap()));
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+fn test_array_slice_bounds() {
+    let code = r#"
+        entry main() {
+            let x: u64[] = [10, 20, 30, 40, 50];
+            assert(x.slice(0..5).len() == 5);
+            assert(x.slice(3..5)[1] == 50);
+            assert(x.slice(2..2).len() == 0);
+            assert(x.slice(5..5).len() == 0);
+            return x.slice(1..3)[0]
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(20));
+
+    let code = r#"
+        entry main() {
+            let x: u64[] = [10, 20, 30];
+            let _: u64[] = x.slice(1..4);
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::OutOfBounds(4, 3)))
+        )
+    );
+
+    let code = r#"
+        entry main() {
+            let x: u64[] = [10, 20, 30];
+            let _: u64[] = x.slice(2..1);
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::InvalidRange(2, 1)))
+        )
+    );
+}
+
+#[test]
+fn test_array_sort_dedup() {
+    let code = r#"
+        entry main() {
+            let values: u64[] = [5, 3, 3, 9, 1, 5];
+            values.sort();
+            assert(values[0] == 1);
+            assert(values[5] == 9);
+            values.dedup();
+            assert(values.len() == 4);
+            assert(values[1] == 3);
+            assert(values[3] == 9);
+
+            let names: string[] = ["bob", "carol", "alice", "bob"];
+            names.sort();
+            assert(names[0] == "alice");
+            assert(names[3] == "carol");
+            names.dedup();
+            assert(names.len() == 3);
+
+            // Only consecutive duplicates are removed
+            let unsorted: u8[] = [1, 2, 1];
+            unsorted.dedup();
+            assert(unsorted.len() == 3);
+
+            // Gas scales with the input size
+            let small: u64[] = [];
+            let large: u64[] = [];
+            for i: u64 = 0; i < 100; i += 1 {
+                if i < 10 {
+                    small.push(10 - i);
+                }
+                large.push(100 - i);
+            }
+
+            let before: u64 = gas_left();
+            small.sort();
+            let small_cost: u64 = before - gas_left();
+
+            let before: u64 = gas_left();
+            large.sort();
+            let large_cost: u64 = before - gas_left();
+
+            assert(small[0] == 1);
+            assert(large[0] == 1);
+            assert(large_cost > small_cost);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+
+    let code = r#"
+        entry main() {
+            let values: u64[][] = [[2], [1]];
+            values.sort();
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::ValueError(_)))
+        )
+    );
+}
+
+#[test]
+fn test_array_index_of() {
+    let code = r#"
+        entry main() {
+            let values: u64[] = [10, 20, 30, 20];
+            assert(values.contains(20));
+            assert(!values.contains(40));
+            assert(values.index_of(20).unwrap() == 1);
+            assert(values.index_of(40).is_none());
+
+            let names: string[] = ["alice", "bob"];
+            assert(names.contains("bob"));
+            assert(!names.contains("carol"));
+            assert(names.index_of("alice").unwrap() == 0);
+            assert(names.index_of("carol").is_none());
+
+            // Scanning stops at the first match
+            let large: u64[] = [];
+            for i: u64 = 0; i < 100; i += 1 {
+                large.push(i);
+            }
+
+            let before: u64 = gas_left();
+            large.index_of(0);
+            let early: u64 = before - gas_left();
+
+            let before: u64 = gas_left();
+            large.index_of(1000);
+            let full: u64 = before - gas_left();
+
+            assert(full > early);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+fn test_map_keys_values() {
+    let code = r#"
+        entry main() {
+            let m: map<string, u64> = {};
+            assert(m.keys().len() == 0);
+            assert(m.values().len() == 0);
+
+            m.insert("c", 3);
+            m.insert("a", 1);
+            m.insert("b", 2);
+
+            // Insertion order is kept
+            let keys: string[] = m.keys();
+            let values: u64[] = m.values();
+            assert(keys.len() == 3);
+            assert(keys[0] == "c");
+            assert(keys[2] == "b");
+            assert(values[0] == 3);
+            assert(values[2] == 2);
+
+            // Overriding a key keeps its position
+            m.insert("c", 30);
+            assert(m.keys()[0] == "c");
+            assert(m.values()[0] == 30);
+
+            m.shift_remove("c");
+            assert(m.keys()[0] == "a");
+            return m.values()[1]
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(2));
+}
+
+#[test]
+fn test_map_merge_extend() {
+    let code = r#"
+        entry main() {
+            let a: map<string, u64> = {};
+            a.insert("x", 1);
+            a.insert("y", 2);
+
+            let b: map<string, u64> = {};
+            b.insert("z", 3);
+
+            // Disjoint keys
+            let merged: map<string, u64> = a.merge(b);
+            assert(merged.len() == 3);
+            assert(a.len() == 2);
+            assert(merged.get("z").unwrap() == 3);
+
+            // Overlapping keys, the other map wins
+            b.insert("x", 10);
+            let merged: map<string, u64> = a.merge(b);
+            assert(merged.len() == 3);
+            assert(merged.get("x").unwrap() == 10);
+            assert(merged.keys()[0] == "x");
+
+            a.extend(b);
+            assert(a.len() == 3);
+            assert(a.get("x").unwrap() == 10);
+            assert(a.get("y").unwrap() == 2);
+            assert(b.len() == 2);
+
+            // Extending with itself is a no-op
+            a.extend(a);
+            assert(a.len() == 3);
+            return a.get("z").unwrap()
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(3));
+
+    // Overriding values doesn't account the keys again
+    let memory_after = |keys: &str| {
+        let code = format!(r#"
+            entry main() {{
+                let a: map<string, string> = {{}};
+                a.insert("first", "value");
+                let b: map<string, string> = {{}};
+                b.insert("{}", "value");
+                a.extend(b);
+                return 0
+            }}
+        "#, keys);
+
+        let (module, environment) = prepare_module(&code);
+        let mut vm = VM::new(&environment);
+        vm.append_module(&module).expect("module");
+        vm.invoke_entry_chunk(0).expect("valid entry chunk");
+        vm.run().unwrap();
+        vm.context().current_memory_usage()
+    };
+
+    assert!(memory_after("other") > memory_after("first"));
+}
+
+#[test]
+fn test_range_collect_step() {
+    let code = r#"
+        entry main() {
+            let values: u64[] = (0..10).collect_step(3);
+            assert(values.len() == 4);
+            assert(values[1] == 3);
+            assert(values[3] == 9);
+
+            // Descending when start is greater than end
+            let values: u64[] = (10..0).collect_step(4);
+            assert(values.len() == 3);
+            assert(values[0] == 10);
+            assert(values[2] == 2);
+
+            assert((5..5).collect_step(1).len() == 0);
+            // Stops before overflowing
+            let bounded: u8[] = (250u8..255u8).collect_step(100u8);
+            assert(bounded.len() == 1);
+
+            let step: u256 = 4;
+            let values: u256[] = (10u256..0u256).collect_step(step);
+            assert(values.len() == 3);
+            assert(values[2] == 2);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+
+    let code = r#"
+        entry main() {
+            let _: u64[] = (0..10).collect_step(0);
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::InvalidParameter))
+        )
+    );
+
+    // Rejected before allocating anything
+    let code = r#"
+        entry main() {
+            let _: u64[] = (0..u64::MAX).collect_step(2);
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::RangeTooLarge))
+        )
+    );
+
+    let code = r#"
+        entry main() {
+            let step: u128 = 1;
+            let _: u128[] = (u128::MAX..0u128).collect_step(step);
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::RangeTooLarge))
+        )
+    );
+
+    // The whole array is charged before being built
+    let code = r#"
+        entry main() {
+            let _: u64[] = (0..1000000).collect_step(1);
+            return 0
+        }
+    "#;
+
+    let (module, environment) = prepare_module(code);
+    let mut vm = VM::new(&environment);
+    vm.append_module(&module).expect("module");
+    vm.invoke_entry_chunk(0).expect("valid entry chunk");
+    vm.context_mut().set_gas_limit(1_000_000);
+
+    assert!(matches!(
+        vm.run(),
+        Err(VMError::EnvironmentError(EnvironmentError::NotEnoughGas { limit: 1_000_000, .. }))
+    ));
+}
+
+#[test]
+fn test_optional_combinators() {
+    let code = r#"
+        entry main() {
+            let some: optional<u64> = 5;
+            let none: optional<u64> = null;
+
+            assert(some.is_some());
+            assert(!some.is_none());
+            assert(none.is_none());
+            assert(!none.is_some());
+
+            assert(some.unwrap_or(10) == 5);
+            assert(none.unwrap_or(10) == 10);
+
+            // Works on values returned by natives too
+            let empty: string[] = [];
+            assert(empty.first().unwrap_or("default") == "default");
+            assert(empty.first().is_none());
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+fn test_string_split_join() {
+    let code = r#"
+        entry main() {
+            let parts: string[] = "a,b,,c".split(",");
+            assert(parts.len() == 4);
+            assert(parts[2] == "");
+            assert(parts.join(",") == "a,b,,c");
+
+            // Trailing separator produces an empty segment
+            let parts: string[] = "a;b;".split(";");
+            assert(parts.len() == 3);
+            assert(parts[2] == "");
+
+            // Multi chars separator
+            let parts: string[] = "one::two::three".split("::");
+            assert(parts.join(" ") == "one two three");
+
+            let empty: string[] = [];
+            assert(empty.join(",") == "");
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+
+    let code = r#"
+        entry main() {
+            let _: string[] = "abc".split("");
+            return 0
+        }
+    "#;
+
+    assert!(
+        matches!(
+            try_run_code(code, 0),
+            Err(VMError::EnvironmentError(EnvironmentError::InvalidParameter))
+        )
+    );
+}
+
+#[test]
+fn test_string_case_and_trim() {
+    let code = r#"
+        entry main() {
+            assert("  hello world  ".trim() == "hello world");
+            // Unicode whitespaces are trimmed too
+            assert("　été ".trim() == "été");
+
+            assert("Héllo".to_uppercase() == "HÉLLO");
+            assert("ÀÉÎ".to_lowercase() == "àéî");
+
+            // Case mapping may change the byte length
+            assert("ß".len() == 2);
+            assert("ß".to_uppercase() == "SS");
+            assert("İ".to_lowercase().len() == 3);
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
+
+#[test]
+fn test_parse_int() {
+    let code = r#"
+        entry main() {
+            assert(parse_int("0").unwrap() == 0);
+            assert(parse_int("42").unwrap() == 42);
+            assert(parse_int("0042").unwrap() == 42);
+            assert(parse_int("18446744073709551615").unwrap() == u64::MAX);
+
+            // Overflow
+            assert(parse_int("18446744073709551616").is_none());
+            // Whitespaces are not trimmed
+            assert(parse_int(" 42").is_none());
+            assert(parse_int("42 ").is_none());
+            assert(parse_int(" 42 ".trim()).unwrap() == 42);
+            // Non numeric input
+            assert(parse_int("").is_none());
+            assert(parse_int("+42").is_none());
+            assert(parse_int("-42").is_none());
+            assert(parse_int("4_2").is_none());
+            assert(parse_int("0x2a").is_none());
+            return 0
+        }
+    "#;
+
+    assert_eq!(run_code(code), Primitive::U64(0));
+}
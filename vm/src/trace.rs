@@ -0,0 +1,274 @@
+#[cfg(feature = "gas-profile")]
+use indexmap::IndexMap;
+use tos_bytecode::OpCode;
+use tos_types::{Primitive, StackValue, ValueCell};
+
+// A single instruction executed by the VM
+// Recorded only when the tracing is enabled
+#[derive(Debug, Clone)]
+pub struct TraceEntry {
+    // Index of the instruction in its chunk
+    ip: usize,
+    // Raw op code executed
+    opcode: u8,
+    // Registers of the chunk before executing the instruction
+    // None for the values that are not snapshotted
+    registers: Vec<Option<Primitive>>,
+}
+
+impl TraceEntry {
+    // Create a new trace entry
+    // Only the scalar registers are copied to keep each entry small:
+    // strings, opaques, bytes, arrays and maps are replaced by None
+    pub fn new(ip: usize, opcode: u8, registers: &[StackValue]) -> Self {
+        let registers = registers.iter()
+            .map(|v| match v.as_ref() {
+                Ok(ValueCell::Default(v)) if !matches!(v, Primitive::String(_) | Primitive::Opaque(_)) => Some(v.clone()),
+                _ => None
+            })
+            .collect();
+
+        Self {
+            ip,
+            opcode,
+            registers
+        }
+    }
+
+    // Get the index of the instruction in its chunk
+    #[inline(always)]
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    // Get the raw op code executed
+    #[inline(always)]
+    pub fn opcode(&self) -> u8 {
+        self.opcode
+    }
+
+    // Get the op code executed if it's a known one
+    #[inline(always)]
+    pub fn op_code(&self) -> Option<OpCode> {
+        OpCode::from_byte(self.opcode)
+    }
+
+    // Get the registers snapshot
+    #[inline(always)]
+    pub fn registers(&self) -> &[Option<Primitive>] {
+        &self.registers
+    }
+}
+
+// Gas consumed by a straight-line run of instructions in a chunk
+// All the executions of the same range are aggregated together
+#[cfg(feature = "gas-profile")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasSegment {
+    // Index of the module containing the chunk
+    module: usize,
+    // Id of the chunk containing the range
+    chunk_id: u16,
+    // Index of the first instruction of the range
+    start: usize,
+    // Index of the last instruction of the range
+    end: usize,
+    // Total gas consumed by the range
+    gas: u64,
+    // How many times the range was executed
+    hits: u64,
+}
+
+#[cfg(feature = "gas-profile")]
+impl GasSegment {
+    // Get the index of the module containing the chunk
+    #[inline(always)]
+    pub fn module(&self) -> usize {
+        self.module
+    }
+
+    // Get the id of the chunk containing the range
+    #[inline(always)]
+    pub fn chunk_id(&self) -> u16 {
+        self.chunk_id
+    }
+
+    // Get the index of the first instruction of the range
+    #[inline(always)]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    // Get the index of the last instruction of the range
+    #[inline(always)]
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    // Get the total gas consumed by the range
+    #[inline(always)]
+    pub fn gas(&self) -> u64 {
+        self.gas
+    }
+
+    // Get how many times the range was executed
+    #[inline(always)]
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+}
+
+// Calls count and gas consumed by a syscall
+#[cfg(feature = "gas-profile")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyscallStats {
+    // Id of the syscall
+    id: u16,
+    // Name of the native function called
+    name: String,
+    // How many times the syscall was called
+    calls: u64,
+    // Total gas consumed by the calls, including the instruction cost
+    gas: u64,
+}
+
+#[cfg(feature = "gas-profile")]
+impl SyscallStats {
+    // Get the id of the syscall
+    #[inline(always)]
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    // Get the name of the native function called
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Get how many times the syscall was called
+    #[inline(always)]
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    // Get the total gas consumed by the calls
+    #[inline(always)]
+    pub fn gas(&self) -> u64 {
+        self.gas
+    }
+}
+
+// Sample the gas consumed by each basic block
+// A block ends after any instruction that may change the control flow
+#[cfg(feature = "gas-profile")]
+#[derive(Debug, Default)]
+pub struct GasProfiler {
+    // Aggregated segments by (module index, chunk id, start, end)
+    segments: IndexMap<(usize, u16, usize, usize), GasSegment>,
+    // Block currently executed
+    current: Option<GasSegment>,
+    // Aggregated stats by syscall id
+    syscalls: IndexMap<u16, SyscallStats>,
+}
+
+#[cfg(feature = "gas-profile")]
+impl GasProfiler {
+    // Record the gas consumed by an instruction
+    pub fn record(&mut self, module: usize, chunk_id: u16, ip: usize, opcode: u8, gas: u64) {
+        match self.current.as_mut() {
+            Some(current) if current.module == module && current.chunk_id == chunk_id => {
+                current.end = ip;
+                current.gas += gas;
+            },
+            _ => {
+                self.flush();
+                self.current = Some(GasSegment {
+                    module,
+                    chunk_id,
+                    start: ip,
+                    end: ip,
+                    gas,
+                    hits: 1
+                });
+            }
+        }
+
+        let ends_block = matches!(
+            OpCode::from_byte(opcode),
+            Some(OpCode::Jump
+                | OpCode::JumpIfFalse
+                | OpCode::Match
+                | OpCode::IteratorNext
+                | OpCode::Return
+                | OpCode::InvokeChunk
+                | OpCode::DynamicCall)
+        );
+
+        if ends_block {
+            self.flush();
+        }
+    }
+
+    // Record the gas consumed by a syscall
+    pub fn record_syscall(&mut self, id: u16, name: &str, gas: u64) {
+        let stats = self.syscalls.entry(id).or_insert_with(|| SyscallStats {
+            id,
+            name: name.to_owned(),
+            calls: 0,
+            gas: 0
+        });
+        stats.calls += 1;
+        stats.gas += gas;
+    }
+
+    // Close the current block and aggregate it
+    pub fn flush(&mut self) {
+        if let Some(segment) = self.current.take() {
+            self.segments.entry((segment.module, segment.chunk_id, segment.start, segment.end))
+                .and_modify(|s| {
+                    s.gas += segment.gas;
+                    s.hits += 1;
+                })
+                .or_insert(segment);
+        }
+    }
+
+    // Drop all the recorded segments
+    pub fn clear(&mut self) {
+        self.segments.clear();
+        self.current = None;
+        self.syscalls.clear();
+    }
+
+    // Get all the aggregated segments in execution order
+    pub fn segments(&self) -> impl Iterator<Item = &GasSegment> {
+        self.segments.values()
+    }
+
+    // Get the stats per syscall in first call order
+    pub fn syscalls(&self) -> impl Iterator<Item = &SyscallStats> {
+        self.syscalls.values()
+    }
+}
+
+#[cfg(all(test, feature = "gas-profile"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_profiler_modules() {
+        let mut profiler = GasProfiler::default();
+        let ret = OpCode::Return.as_byte();
+
+        // Same chunk and range, but in two different modules
+        profiler.record(0, 0, 0, ret, 5);
+        profiler.record(1, 0, 0, ret, 7);
+        profiler.record(1, 0, 0, ret, 7);
+
+        let segments = profiler.segments().collect::<Vec<_>>();
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].module(), segments[0].gas(), segments[0].hits()), (0, 5, 1));
+        assert_eq!((segments[1].module(), segments[1].gas(), segments[1].hits()), (1, 14, 2));
+    }
+}